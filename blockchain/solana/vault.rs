@@ -1,40 +1,236 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
 use solana_program::{
+    ed25519_program,
     keccak::hashv,
+    program::set_return_data,
+    program_option::COption,
     program_error::ProgramError,
-    sysvar::{clock::Clock, rent::Rent},
+    secp256k1_recover::secp256k1_recover,
+    sysvar::{
+        self,
+        clock::Clock,
+        instructions::{load_current_index_checked, load_instruction_at_checked},
+        rent::Rent,
+    },
 };
+use pyth_sdk_solana::load_price_feed_from_account_info;
 use std::mem::size_of;
 
 declare_id!("yes-no.funvau1txxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
 
+/// Layout version written to every vault at initialization
+pub const CURRENT_PROGRAM_VERSION: u16 = 1;
+
+// Vault feature flag bits. Optional behaviors are off unless the vault authority enables them.
+/// Bit 0: markets may be created as probabilistic (`resolve_market_probabilistic`)
+pub const FEATURE_PROBABILISTIC_MARKETS: u64 = 1 << 0;
+/// Bit 1: markets may insure part of losing stakes (`insured_basis_points`)
+pub const FEATURE_INSURED_MARKETS: u64 = 1 << 1;
+/// Bit 2: winnings may be claimed into a vesting escrow (`claim_winnings_vested`)
+pub const FEATURE_VESTED_CLAIMS: u64 = 1 << 2;
+/// Bit 3: two-phase bets through `verify_and_reserve` / `place_bet_reserved`
+pub const FEATURE_BET_RESERVATIONS: u64 = 1 << 3;
+
+// `health_check` status bits. A set bit flags a violated invariant; zero means all clear.
+/// Bit 0: retained fees and pending donations exceed the vault token balance
+pub const HEALTH_FEES_EXCEED_BALANCE: u32 = 1 << 0;
+/// Bit 1: recorded LP liquidity exceeds the vault token balance
+pub const HEALTH_LIQUIDITY_EXCEEDS_BALANCE: u32 = 1 << 1;
+/// Bit 2: fees, donations and liquidity together exceed the balance, or overflow when summed
+pub const HEALTH_OBLIGATIONS_EXCEED_BALANCE: u32 = 1 << 2;
+/// Bit 3: fees and donations exceed the total volume they were taken from
+pub const HEALTH_FEES_EXCEED_VOLUME: u32 = 1 << 3;
+/// Bit 4: the token account does not hold the vault's settlement mint
+pub const HEALTH_SETTLEMENT_MINT_MISMATCH: u32 = 1 << 4;
+/// Bit 5: the vault layout is newer than this program understands
+pub const HEALTH_UNKNOWN_PROGRAM_VERSION: u32 = 1 << 5;
+
+/// Default fee ceiling (basis points) until governance configures a different one
+pub const MAX_FEE_BPS: u16 = 1000;
+
+/// Default minimum gap (seconds) between market creation and resolution. Validator clocks
+/// can drift by several seconds, so resolutions closer than this are rejected outright
+/// rather than accepted or rejected depending on which validator processes the creation.
+pub const DEFAULT_MIN_RESOLUTION_OFFSET_SECONDS: i64 = 60;
+
+/// Default share of a community-resolved market's total stake that must vote before
+/// the outcome is final
+pub const DEFAULT_COMMUNITY_QUORUM_BPS: u16 = 5_000;
+
+/// Default time after `resolution_time` that bettors have to reach quorum before a
+/// community-resolved market is voided
+pub const DEFAULT_COMMUNITY_VOTE_WINDOW_SECONDS: u64 = 86_400;
+
+/// Most mutually exclusive outcomes a market may have; bounds the `Market` account size
+pub const MAX_OUTCOMES: usize = 8;
+
+/// Default time after `resolution_time` an unresolved market must wait before it can be
+/// cancelled and refunded
+pub const DEFAULT_CANCELLATION_GRACE_SECONDS: u64 = 7 * 86_400;
+
+/// Default time after an oracle resolution during which a bettor may dispute it; claims
+/// open only once it has passed undisputed
+pub const DEFAULT_CHALLENGE_WINDOW_SECONDS: u64 = 86_400;
+
+/// Default bound on how long past `resolution_time` the vault's configured windows may keep
+/// funds locked in total
+pub const DEFAULT_MAX_TOTAL_LOCKUP_SECONDS: u64 = 30 * 86_400;
+
+/// Default bound on allowlist merkle proof depth; 24 levels cover ~16.7M addresses
+pub const DEFAULT_MAX_MERKLE_DEPTH: u8 = 24;
+
+/// Maximum age (seconds) of a reference price snapshotted at resolution
+pub const MAX_PRICE_AGE_SECONDS: u64 = 60;
+
+/// Most (market, bet) pairs `claim_winnings_batch` settles in one transaction
+pub const MAX_CLAIM_BATCH_SIZE: usize = 8;
+
+/// Largest market page that fits the 1024-byte return data limit (4-byte length + 64 bytes per entry)
+pub const MAX_MARKET_PAGE_SIZE: usize = 15;
+
+/// Largest bettor history that fits the return data limit (4-byte length + 32 bytes per bet,
+/// plus 28 bytes of summary stats)
+pub const MAX_BETTOR_HISTORY_SIZE: usize = 31;
+
+/// Depth of a market's entitlement merkle tree, bounding it to 2^16 bets
+pub const ENTITLEMENT_TREE_DEPTH: usize = 16;
+
+/// Settlement mints a vault may accept in addition to its own `settlement_mint`
+pub const MAX_EXTRA_SETTLEMENT_MINTS: usize = 4;
+
+/// Ceiling (basis points) on the per-bet keeper tip
+pub const MAX_KEEPER_TIP_BPS: u16 = 100;
+
+/// Lower bound (basis points) for any stored implied probability
+pub const MIN_PROBABILITY_BPS: u64 = 1;
+/// Upper bound (basis points) for any stored implied probability
+pub const MAX_PROBABILITY_BPS: u64 = 9_999;
+
 /// yes-no.fun Vault Program
 /// Implements secure, non-custodial betting vault with advanced cryptographic features
 #[program]
 pub mod yes-no_vault {
     use super::*;
 
-    /// Initialize the vault with merkle root verification
+    /// Initialize the program-wide configuration with the default fee ceiling
+    pub fn initialize_program_config(ctx: Context<InitializeProgramConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.authority.key();
+        config.max_fee_basis_points = MAX_FEE_BPS;
+
+        emit!(MaxFeeUpdated {
+            max_fee_basis_points: MAX_FEE_BPS,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Tune the fee ceiling applied to every fee setter without a redeploy
+    pub fn set_max_fee_basis_points(
+        ctx: Context<SetMaxFeeBasisPoints>,
+        max_fee_basis_points: u16,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(max_fee_basis_points <= 10_000, ErrorCode::InvalidFee);
+
+        config.max_fee_basis_points = max_fee_basis_points;
+
+        emit!(MaxFeeUpdated {
+            max_fee_basis_points,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Initialize the vault with merkle root verification. `fee_curve` defaults to a fixed
+    /// fee; a linear curve raises each market's fee from `fee_basis_points` at creation to
+    /// `max_fee_basis_points` at resolution, which defaults to the base fee and is ignored
+    /// for a fixed fee.
     pub fn initialize_vault(
         ctx: Context<InitializeVault>,
         merkle_root: [u8; 32],
         fee_basis_points: u16,
         min_bet_amount: u64,
+        max_fee_basis_points: Option<u16>,
+        fee_curve: Option<FeeCurve>,
     ) -> Result<()> {
-        require!(fee_basis_points <= 1000, ErrorCode::InvalidFee);
+        let (fee_curve, fee_curve_max_basis_points) = validate_fee_curve(
+            fee_basis_points,
+            max_fee_basis_points,
+            fee_curve.unwrap_or(FeeCurve::Fixed),
+            &ctx.accounts.config,
+        )?;
         require!(min_bet_amount > 0, ErrorCode::InvalidMinBet);
 
         let vault = &mut ctx.accounts.vault;
         vault.authority = ctx.accounts.authority.key();
         vault.merkle_root = merkle_root;
+        vault.allowlist_enabled = merkle_root != [0u8; 32];
         vault.fee_basis_points = fee_basis_points;
+        vault.fee_curve = fee_curve;
+        vault.fee_curve_max_basis_points = fee_curve_max_basis_points;
         vault.min_bet_amount = min_bet_amount;
         vault.total_volume = 0;
         vault.total_fees_collected = 0;
         vault.nonce = 0;
         vault.is_paused = false;
-        vault.creation_timestamp = Clock::get()?.unix_timestamp;
+        vault.creation_timestamp = current_timestamp()?;
+        vault.donation_basis_points = 0;
+        vault.donation_recipient = Pubkey::default();
+        vault.pending_donations = 0;
+        vault.total_donations_distributed = 0;
+        vault.early_exit_fee_basis_points = 0;
+        vault.total_liquidity_locked = 0;
+        vault.market_count = 0;
+        vault.resolution_extension_seconds = 0;
+        vault.max_resolution_extensions = 0;
+        vault.max_total_extension_seconds = 0;
+        vault.allow_self_oracle = true;
+        vault.settlement_mint = ctx.accounts.settlement_mint.key();
+        vault.min_resolution_offset_seconds = DEFAULT_MIN_RESOLUTION_OFFSET_SECONDS;
+        vault.program_version = CURRENT_PROGRAM_VERSION;
+        vault.feature_flags = 0;
+        vault.extra_settlement_mints = [Pubkey::default(); MAX_EXTRA_SETTLEMENT_MINTS];
+        vault.extra_settlement_fees_collected = [0; MAX_EXTRA_SETTLEMENT_MINTS];
+        vault.creator_policy = CreatorPolicy::Permissionless;
+        vault.burn_basis_points = 0;
+        vault.max_merkle_depth = DEFAULT_MAX_MERKLE_DEPTH;
+        vault.bet_count = 0;
+        vault.volume_reconcile_cursor = 0;
+        vault.volume_reconcile_sum = 0;
+        vault.min_initial_liquidity = 0;
+        vault.privacy_enabled = true;
+        vault.keeper_tip_basis_points = 0;
+        vault.max_liquidity = 0;
+        vault.survey_stake = 0;
+        vault.winnings_fee_basis_points = 0;
+        vault.winnings_fee_basis = WinningsFeeBasis::Profit;
+        vault.new_markets_paused = false;
+        vault.mint_decimals = ctx.accounts.settlement_mint.decimals;
+        vault.extra_settlement_mint_decimals = [0; MAX_EXTRA_SETTLEMENT_MINTS];
+        vault.oracle_signature_scheme = OracleSignatureScheme::Ed25519;
+        vault.balancing_rebate_basis_points = 0;
+        vault.wind_down = false;
+        vault.community_quorum_basis_points = DEFAULT_COMMUNITY_QUORUM_BPS;
+        vault.community_vote_window_seconds = DEFAULT_COMMUNITY_VOTE_WINDOW_SECONDS;
+        vault.max_open_markets_per_creator = 0;
+        vault.authority_override_window_seconds = 0;
+        vault.odds_scaled_min_threshold_bps = 0;
+        vault.odds_scaled_min_max_multiplier = 1;
+        vault.total_fees_withdrawn = 0;
+        vault.max_total_lockup_seconds = DEFAULT_MAX_TOTAL_LOCKUP_SECONDS;
+        vault.cancellation_grace_seconds = DEFAULT_CANCELLATION_GRACE_SECONDS;
+        vault.claim_window_seconds = 0;
+        vault.challenge_window_seconds = DEFAULT_CHALLENGE_WINDOW_SECONDS;
+        vault.dispute_bond_amount = min_bet_amount;
+        require_lockup_horizon(vault)?;
 
         emit!(VaultInitialized {
             vault: vault.key(),
@@ -46,571 +242,8781 @@ pub mod yes-no_vault {
         Ok(())
     }
 
-    /// Create a new prediction market with commit-reveal mechanism
+    /// Create a new prediction market with commit-reveal mechanism. Passing no
+    /// `commitment_hash` opts a trusted oracle out of commit-reveal, so resolution relies on
+    /// the oracle signature alone. `allow_early_resolution` lets the oracle resolve as soon
+    /// as the event concludes instead of waiting for `resolution_time`. `outcome_count` is 2
+    /// for a Yes/No market, or up to `MAX_OUTCOMES` mutually exclusive outcomes; passing an
+    /// `outcome_schema` account labels them with one of the vault's outcome sets.
     pub fn create_market(
         ctx: Context<CreateMarket>,
         market_id: [u8; 32],
         resolution_time: i64,
         oracle_pubkey: Pubkey,
-        commitment_hash: [u8; 32],
+        commitment_hash: Option<[u8; 32]>,
+        is_probabilistic: bool,
+        opening_time: i64,
+        min_seed_per_outcome: u64,
+        insured_basis_points: u16,
+        price_reference_account: Option<Pubkey>,
+        creator_seed_fee_exempt: bool,
+        settlement_mint: Option<Pubkey>,
+        initial_liquidity: u64,
+        community_resolved: bool,
+        allow_early_resolution: bool,
+        outcome_count: u8,
     ) -> Result<()> {
-        let clock = Clock::get()?;
+        let now = current_timestamp()?;
+        check_market_params(
+            &ctx.accounts.vault,
+            &ctx.accounts.creator.key(),
+            &ctx.accounts.creator_allowlist_entry,
+            resolution_time,
+            &oracle_pubkey,
+            now,
+        )?;
         require!(
-            resolution_time > clock.unix_timestamp,
-            ErrorCode::InvalidResolutionTime
+            opening_time < resolution_time,
+            ErrorCode::InvalidOpeningTime
+        );
+        require!(
+            insured_basis_points <= 10_000,
+            ErrorCode::InvalidInsurance
+        );
+        require!(
+            (2..=MAX_OUTCOMES as u8).contains(&outcome_count),
+            ErrorCode::InvalidOutcomeCount
+        );
+        // Probabilistic splits and insurance are defined over Yes/No only
+        require!(
+            outcome_count == 2 || !is_probabilistic && insured_basis_points == 0,
+            ErrorCode::InvalidOutcomeCount
+        );
+        let outcome_schema = match &ctx.accounts.outcome_schema {
+            Some(schema) => {
+                require_outcome_schema(schema, &ctx.accounts.vault.key(), outcome_count)?;
+                schema.key()
+            }
+            None => Pubkey::default(),
+        };
+
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            initial_liquidity >= vault.min_initial_liquidity,
+            ErrorCode::InsufficientInitialLiquidity
+        );
+        if is_probabilistic {
+            require_feature(vault, FEATURE_PROBABILISTIC_MARKETS)?;
+        }
+        if insured_basis_points > 0 {
+            require_feature(vault, FEATURE_INSURED_MARKETS)?;
+        }
+        // Bettor votes pick a single winner or void the market, leaving nothing to settle a
+        // probabilistic split or an insurance deposit against
+        require!(
+            !community_resolved || (!is_probabilistic && insured_basis_points == 0),
+            ErrorCode::InvalidCommunityMarket
         );
+        // Voting opens at `resolution_time`, so a community market has no early path
+        require!(
+            !(community_resolved && allow_early_resolution),
+            ErrorCode::InvalidCommunityMarket
+        );
+        // Markets settle in the vault's own mint unless an accepted override is given
+        let settlement_mint = settlement_mint.unwrap_or(vault.settlement_mint);
+        require!(
+            is_settlement_mint(vault, &settlement_mint),
+            ErrorCode::UnsupportedSettlementMint
+        );
+        open_creator_market(&mut ctx.accounts.creator_stats, vault, ctx.accounts.creator.key())?;
+        let sequence = vault.market_count;
+        vault.market_count = sequence.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
 
         let market = &mut ctx.accounts.market;
-        market.id = market_id;
-        market.vault = ctx.accounts.vault.key();
-        market.creator = ctx.accounts.creator.key();
-        market.oracle = oracle_pubkey;
-        market.resolution_time = resolution_time;
-        market.commitment_hash = commitment_hash;
-        market.total_yes_amount = 0;
-        market.total_no_amount = 0;
-        market.is_resolved = false;
-        market.winning_outcome = None;
-        market.creation_timestamp = clock.unix_timestamp;
-        market.liquidity_locked = 0;
-
-        // Calculate initial probability from AMM curve
-        market.implied_probability = calculate_initial_probability(
-            market.total_yes_amount,
-            market.total_no_amount,
+        initialize_market(
+            market,
+            MarketParams {
+                id: market_id,
+                vault: vault.key(),
+                creator: ctx.accounts.creator.key(),
+                oracle: oracle_pubkey,
+                resolution_time,
+                commitment_hash,
+                fee_basis_points: vault.fee_basis_points,
+                fee_curve: vault.fee_curve,
+                fee_curve_max_basis_points: vault.fee_curve_max_basis_points,
+                min_bet_amount: vault.min_bet_amount,
+                is_probabilistic,
+                opening_time,
+                min_seed_per_outcome,
+                sequence,
+                insured_basis_points,
+                price_reference_account,
+                creator_seed_fee_exempt,
+                settlement_mint,
+                mint_decimals: settlement_mint_decimals(vault, &settlement_mint)?,
+                oracle_signature_scheme: vault.oracle_signature_scheme,
+                // Vote finalization has no burn or keeper payout step
+                burn_basis_points: if community_resolved { 0 } else { vault.burn_basis_points },
+                privacy_enabled: vault.privacy_enabled,
+                keeper_tip_basis_points: if community_resolved {
+                    0
+                } else {
+                    vault.keeper_tip_basis_points
+                },
+                max_liquidity: vault.max_liquidity,
+                community_resolved,
+                community_quorum_basis_points: vault.community_quorum_basis_points,
+                community_vote_window_seconds: vault.community_vote_window_seconds,
+                allow_early_resolution,
+                outcome_count,
+                outcome_schema,
+            },
+            now,
         );
 
+        // Seed LP liquidity in the same transaction so the market opens priced and tradable
+        if initial_liquidity > 0 {
+            let creator_token_account = ctx
+                .accounts
+                .creator_token_account
+                .as_ref()
+                .ok_or(ErrorCode::MissingLiquidityAccounts)?;
+            let vault_token_account = ctx
+                .accounts
+                .vault_token_account
+                .as_ref()
+                .ok_or(ErrorCode::MissingLiquidityAccounts)?;
+            require_vault_token_account(vault_token_account, &vault.key())?;
+            let token_program = ctx
+                .accounts
+                .token_program
+                .as_ref()
+                .ok_or(ErrorCode::MissingLiquidityAccounts)?;
+            require_settlement_mint(market, &[creator_token_account, vault_token_account])?;
+            require_liquidity_within_cap(market, initial_liquidity)?;
+            let lp_mint = ctx
+                .accounts
+                .lp_mint
+                .as_ref()
+                .ok_or(ErrorCode::MissingLiquidityAccounts)?;
+            let creator_lp_token_account = ctx
+                .accounts
+                .creator_lp_token_account
+                .as_ref()
+                .ok_or(ErrorCode::MissingLiquidityAccounts)?;
+            require_lp_mint(market, lp_mint, &vault.key())?;
+            require_lp_token_account(
+                market,
+                creator_lp_token_account,
+                &ctx.accounts.creator.key(),
+            )?;
+
+            let cpi_accounts = Transfer {
+                from: creator_token_account.to_account_info(),
+                to: vault_token_account.to_account_info(),
+                authority: ctx.accounts.creator.to_account_info(),
+            };
+            token::transfer(
+                CpiContext::new(token_program.to_account_info(), cpi_accounts),
+                initial_liquidity,
+            )?;
+
+            market.liquidity_locked = initial_liquidity;
+            market.total_lp_tokens = calculate_lp_tokens(initial_liquidity, 0, 0)?;
+            mint_lp_tokens(
+                vault,
+                lp_mint,
+                creator_lp_token_account,
+                token_program,
+                market.total_lp_tokens,
+            )?;
+            market.is_tradable = true;
+            refresh_implied_probability(market);
+            vault.total_liquidity_locked = vault
+                .total_liquidity_locked
+                .checked_add(initial_liquidity)
+                .ok_or(ErrorCode::MathOverflow)?;
+
+            emit!(LiquidityAdded {
+                market: market.key(),
+                provider: ctx.accounts.creator.key(),
+                amount: initial_liquidity,
+                lp_tokens: market.total_lp_tokens,
+                total_liquidity_locked: vault.total_liquidity_locked,
+                implied_probability: market.implied_probability,
+                mint_decimals: market.mint_decimals,
+                timestamp: now,
+            });
+        }
+
         emit!(MarketCreated {
             market: market.key(),
             market_id,
             creator: market.creator,
             resolution_time,
-            timestamp: clock.unix_timestamp,
+            self_oracle: market.self_oracle,
+            outcome_count: market.outcome_count,
+            outcome_schema: market.outcome_schema,
+            timestamp: now,
         });
+        emit_lifecycle_event(market, None, ctx.accounts.creator.key(), now);
 
         Ok(())
     }
 
-    /// Place a bet with cryptographic proof
-    pub fn place_bet(
-        ctx: Context<PlaceBet>,
-        amount: u64,
-        outcome: Outcome,
-        proof: Vec<u8>,
-        nullifier: [u8; 32],
+    /// Dry-run the core `create_market` checks plus the fee ceiling without creating
+    /// anything. Returns a `MarketParamsCheck` via return data whose `error_code` is the
+    /// program error `create_market` would fail with, or 0 when the parameters pass.
+    pub fn validate_market_params(
+        ctx: Context<ValidateMarketParams>,
+        resolution_time: i64,
+        oracle: Pubkey,
+        fee_basis_points: u16,
     ) -> Result<()> {
-        let vault = &ctx.accounts.vault;
-        let market = &mut ctx.accounts.market;
-        let clock = Clock::get()?;
+        let now = current_timestamp()?;
+        let outcome = check_market_params(
+            &ctx.accounts.vault,
+            &ctx.accounts.creator.key(),
+            &ctx.accounts.creator_allowlist_entry,
+            resolution_time,
+            &oracle,
+            now,
+        )
+        .and_then(|()| {
+            if fee_basis_points > ctx.accounts.config.max_fee_basis_points {
+                Err(ErrorCode::InvalidFee)
+            } else {
+                Ok(())
+            }
+        });
 
-        // Verify betting is still open
-        require!(!market.is_resolved, ErrorCode::MarketResolved);
+        let check = match outcome {
+            Ok(()) => MarketParamsCheck {
+                valid: true,
+                error_code: 0,
+            },
+            Err(error) => MarketParamsCheck {
+                valid: false,
+                error_code: anchor_lang::error::ERROR_CODE_OFFSET + error as u32,
+            },
+        };
+        set_return_data(&check.try_to_vec()?);
+
+        Ok(())
+    }
+
+    /// Create a reusable market template holding common creation parameters
+    pub fn create_market_template(
+        ctx: Context<CreateMarketTemplate>,
+        oracle_pubkey: Pubkey,
+        default_duration: u64,
+        fee_basis_points: u16,
+        min_bet_amount: u64,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
         require!(
-            clock.unix_timestamp < market.resolution_time,
-            ErrorCode::MarketClosed
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
         );
-        require!(amount >= vault.min_bet_amount, ErrorCode::BetTooSmall);
+        validate_template_params(
+            default_duration,
+            fee_basis_points,
+            min_bet_amount,
+            ctx.accounts.config.max_fee_basis_points,
+        )?;
 
-        // Verify merkle proof for allowlist (if applicable)
-        if vault.merkle_root != [0u8; 32] {
-            verify_merkle_proof(
-                &proof,
-                vault.merkle_root,
-                ctx.accounts.bettor.key(),
-            )?;
-        }
+        let template = &mut ctx.accounts.template;
+        template.vault = vault.key();
+        template.oracle = oracle_pubkey;
+        template.default_duration = default_duration;
+        template.fee_basis_points = fee_basis_points;
+        template.min_bet_amount = min_bet_amount;
+
+        emit!(MarketTemplateUpdated {
+            template: template.key(),
+            oracle: oracle_pubkey,
+            default_duration,
+            fee_basis_points,
+            min_bet_amount,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
 
-        // Verify nullifier hasn't been used (prevent double-spending)
+    /// Update the parameters of an existing market template
+    pub fn update_market_template(
+        ctx: Context<UpdateMarketTemplate>,
+        oracle_pubkey: Pubkey,
+        default_duration: u64,
+        fee_basis_points: u16,
+        min_bet_amount: u64,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let template = &mut ctx.accounts.template;
         require!(
-            !ctx.accounts.nullifier_account.is_used,
-            ErrorCode::NullifierAlreadyUsed
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
         );
-        ctx.accounts.nullifier_account.is_used = true;
-        ctx.accounts.nullifier_account.nullifier = nullifier;
+        require!(template.vault == vault.key(), ErrorCode::InvalidTemplate);
+        validate_template_params(
+            default_duration,
+            fee_basis_points,
+            min_bet_amount,
+            ctx.accounts.config.max_fee_basis_points,
+        )?;
 
-        // Calculate fees
-        let fee_amount = (amount as u128 * vault.fee_basis_points as u128 / 10_000) as u64;
-        let bet_amount = amount - fee_amount;
+        template.oracle = oracle_pubkey;
+        template.default_duration = default_duration;
+        template.fee_basis_points = fee_basis_points;
+        template.min_bet_amount = min_bet_amount;
 
-        // Transfer tokens to vault
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.bettor_token_account.to_account_info(),
-            to: ctx.accounts.vault_token_account.to_account_info(),
-            authority: ctx.accounts.bettor.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
+        emit!(MarketTemplateUpdated {
+            template: template.key(),
+            oracle: oracle_pubkey,
+            default_duration,
+            fee_basis_points,
+            min_bet_amount,
+            timestamp: current_timestamp()?,
+        });
 
-        // Update market state
-        match outcome {
-            Outcome::Yes => market.total_yes_amount += bet_amount,
-            Outcome::No => market.total_no_amount += bet_amount,
-        }
+        Ok(())
+    }
 
-        // Update implied probability using LMSR (Logarithmic Market Scoring Rule)
-        market.implied_probability = calculate_lmsr_probability(
-            market.total_yes_amount,
-            market.total_no_amount,
-            market.liquidity_locked,
+    /// Create a market inheriting oracle, duration, fees and limits from a template
+    pub fn create_market_from_template(
+        ctx: Context<CreateMarketFromTemplate>,
+        market_id: [u8; 32],
+        commitment_hash: [u8; 32],
+    ) -> Result<()> {
+        let now = current_timestamp()?;
+        let vault = &mut ctx.accounts.vault;
+        let template = &ctx.accounts.template;
+        require!(!vault.new_markets_paused, ErrorCode::NewMarketsPaused);
+        require_inflows_open(vault)?;
+        require!(template.vault == vault.key(), ErrorCode::InvalidTemplate);
+        require_creator_allowed(
+            vault,
+            &ctx.accounts.creator.key(),
+            &ctx.accounts.creator_allowlist_entry,
+        )?;
+        require!(
+            vault.allow_self_oracle || ctx.accounts.creator.key() != template.oracle,
+            ErrorCode::SelfOracleNotAllowed
         );
 
-        // Record bet
-        let bet_account = &mut ctx.accounts.bet_account;
-        bet_account.market = market.key();
-        bet_account.bettor = ctx.accounts.bettor.key();
-        bet_account.amount = bet_amount;
-        bet_account.outcome = outcome;
-        bet_account.timestamp = clock.unix_timestamp;
-        bet_account.odds = market.implied_probability;
-        bet_account.nullifier = nullifier;
-        bet_account.is_claimed = false;
+        let resolution_time = checked_add_seconds(now, template.default_duration)?;
+        require!(
+            resolution_time - now >= vault.min_resolution_offset_seconds,
+            ErrorCode::ResolutionTooSoon
+        );
 
-        // Update vault statistics
-        let vault = &mut ctx.accounts.vault;
-        vault.total_volume += amount;
-        vault.total_fees_collected += fee_amount;
+        open_creator_market(&mut ctx.accounts.creator_stats, vault, ctx.accounts.creator.key())?;
+        let sequence = vault.market_count;
+        vault.market_count = sequence.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
 
-        emit!(BetPlaced {
+        let market = &mut ctx.accounts.market;
+        initialize_market(
+            market,
+            MarketParams {
+                id: market_id,
+                vault: vault.key(),
+                creator: ctx.accounts.creator.key(),
+                oracle: template.oracle,
+                resolution_time,
+                commitment_hash: Some(commitment_hash),
+                fee_basis_points: template.fee_basis_points,
+                fee_curve: vault.fee_curve,
+                fee_curve_max_basis_points: vault.fee_curve_max_basis_points,
+                min_bet_amount: template.min_bet_amount,
+                is_probabilistic: false,
+                opening_time: now,
+                min_seed_per_outcome: 0,
+                sequence,
+                insured_basis_points: 0,
+                price_reference_account: None,
+                creator_seed_fee_exempt: false,
+                settlement_mint: vault.settlement_mint,
+                mint_decimals: vault.mint_decimals,
+                oracle_signature_scheme: vault.oracle_signature_scheme,
+                burn_basis_points: vault.burn_basis_points,
+                privacy_enabled: vault.privacy_enabled,
+                keeper_tip_basis_points: vault.keeper_tip_basis_points,
+                max_liquidity: vault.max_liquidity,
+                community_resolved: false,
+                community_quorum_basis_points: vault.community_quorum_basis_points,
+                community_vote_window_seconds: vault.community_vote_window_seconds,
+                allow_early_resolution: false,
+                outcome_count: 2,
+                outcome_schema: Pubkey::default(),
+            },
+            now,
+        );
+
+        emit!(MarketCreated {
             market: market.key(),
-            bettor: ctx.accounts.bettor.key(),
-            amount: bet_amount,
-            outcome,
-            odds: market.implied_probability,
-            timestamp: clock.unix_timestamp,
+            market_id,
+            creator: market.creator,
+            resolution_time,
+            self_oracle: market.self_oracle,
+            outcome_count: market.outcome_count,
+            outcome_schema: market.outcome_schema,
+            timestamp: now,
         });
+        emit_lifecycle_event(market, None, ctx.accounts.creator.key(), now);
 
         Ok(())
     }
 
-    /// Resolve market with oracle verification
-    pub fn resolve_market(
-        ctx: Context<ResolveMarket>,
-        winning_outcome: Outcome,
-        oracle_signature: Vec<u8>,
-        reveal_value: [u8; 32],
+    /// Create the next market in a recurring series, inheriting oracle, fees, limits
+    /// and flags from an existing market while starting from fresh pools
+    pub fn clone_market(
+        ctx: Context<CloneMarket>,
+        new_market_id: [u8; 32],
+        new_resolution_time: i64,
+        new_commitment_hash: [u8; 32],
     ) -> Result<()> {
-        let market = &mut ctx.accounts.market;
-        let clock = Clock::get()?;
-
-        require!(!market.is_resolved, ErrorCode::MarketAlreadyResolved);
+        let now = current_timestamp()?;
+        let vault = &mut ctx.accounts.vault;
+        let source = &ctx.accounts.source_market;
+        require!(!vault.new_markets_paused, ErrorCode::NewMarketsPaused);
+        require_inflows_open(vault)?;
+        require!(source.vault == vault.key(), ErrorCode::InvalidMarketVault);
+        require_creator_allowed(
+            vault,
+            &ctx.accounts.creator.key(),
+            &ctx.accounts.creator_allowlist_entry,
+        )?;
         require!(
-            clock.unix_timestamp >= market.resolution_time,
-            ErrorCode::TooEarlyToResolve
+            ctx.accounts.creator.key() == source.creator
+                || ctx.accounts.creator.key() == vault.authority,
+            ErrorCode::Unauthorized
         );
         require!(
-            ctx.accounts.oracle.key() == market.oracle,
-            ErrorCode::UnauthorizedOracle
+            new_resolution_time > now,
+            ErrorCode::InvalidResolutionTime
         );
-
-        // Verify commit-reveal
-        let computed_hash = hashv(&[&reveal_value]);
         require!(
-            computed_hash.to_bytes() == market.commitment_hash,
-            ErrorCode::InvalidReveal
+            new_resolution_time - now >= vault.min_resolution_offset_seconds,
+            ErrorCode::ResolutionTooSoon
+        );
+        if source.is_probabilistic {
+            require_feature(vault, FEATURE_PROBABILISTIC_MARKETS)?;
+        }
+        if source.insured_basis_points > 0 {
+            require_feature(vault, FEATURE_INSURED_MARKETS)?;
+        }
+        require!(
+            vault.allow_self_oracle || ctx.accounts.creator.key() != source.oracle,
+            ErrorCode::SelfOracleNotAllowed
         );
 
-        // Verify oracle signature
-        verify_oracle_signature(
-            &oracle_signature,
-            &market.id,
-            winning_outcome,
-            &ctx.accounts.oracle.key(),
-        )?;
+        open_creator_market(&mut ctx.accounts.creator_stats, vault, ctx.accounts.creator.key())?;
+        let sequence = vault.market_count;
+        vault.market_count = sequence.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
 
-        market.is_resolved = true;
-        market.winning_outcome = Some(winning_outcome);
-        market.resolution_timestamp = clock.unix_timestamp;
+        let market = &mut ctx.accounts.market;
+        initialize_market(
+            market,
+            MarketParams {
+                id: new_market_id,
+                vault: vault.key(),
+                creator: ctx.accounts.creator.key(),
+                oracle: source.oracle,
+                resolution_time: new_resolution_time,
+                // A series keeps its source's commit-reveal mode
+                commitment_hash: source.commit_reveal_enabled.then_some(new_commitment_hash),
+                fee_basis_points: source.fee_basis_points,
+                fee_curve: source.fee_curve,
+                fee_curve_max_basis_points: source.fee_curve_max_basis_points,
+                min_bet_amount: source.min_bet_amount,
+                is_probabilistic: source.is_probabilistic,
+                opening_time: now,
+                min_seed_per_outcome: source.min_seed_per_outcome,
+                sequence,
+                insured_basis_points: source.insured_basis_points,
+                price_reference_account: source.price_reference_account,
+                creator_seed_fee_exempt: source.creator_seed_fee_exempt,
+                settlement_mint: source.settlement_mint,
+                mint_decimals: source.mint_decimals,
+                oracle_signature_scheme: source.oracle_signature_scheme,
+                burn_basis_points: source.burn_basis_points,
+                privacy_enabled: source.privacy_enabled,
+                keeper_tip_basis_points: source.keeper_tip_basis_points,
+                max_liquidity: source.max_liquidity,
+                community_resolved: source.community_resolved,
+                community_quorum_basis_points: source.community_quorum_basis_points,
+                community_vote_window_seconds: source.community_vote_window_seconds,
+                allow_early_resolution: source.allow_early_resolution,
+                outcome_count: source.outcome_count,
+                outcome_schema: source.outcome_schema,
+            },
+            now,
+        );
 
-        emit!(MarketResolved {
+        emit!(MarketCreated {
             market: market.key(),
-            winning_outcome,
-            total_yes: market.total_yes_amount,
-            total_no: market.total_no_amount,
-            timestamp: clock.unix_timestamp,
+            market_id: new_market_id,
+            creator: market.creator,
+            resolution_time: new_resolution_time,
+            self_oracle: market.self_oracle,
+            outcome_count: market.outcome_count,
+            outcome_schema: market.outcome_schema,
+            timestamp: now,
+        });
+        emit_lifecycle_event(market, None, ctx.accounts.creator.key(), now);
+        emit!(MarketCloned {
+            market: market.key(),
+            source_market: source.key(),
+            timestamp: now,
         });
 
         Ok(())
     }
 
-    /// Claim winnings with zero-knowledge proof
-    pub fn claim_winnings(
-        ctx: Context<ClaimWinnings>,
+    /// Place a bet with cryptographic proof on the outcome at `outcome_index`. In a Yes/No
+    /// market the index is the `Outcome` discriminant, which encodes identically.
+    pub fn place_bet(
+        ctx: Context<PlaceBet>,
+        amount: u64,
+        outcome_index: u8,
         proof: Vec<u8>,
+        nullifier: [u8; 32],
+        nullifier_secret: [u8; 32],
+        memo: [u8; 16],
     ) -> Result<()> {
-        let bet = &mut ctx.accounts.bet_account;
+        log_compute_units("place_bet:start");
+        let vault = &ctx.accounts.vault;
         let market = &ctx.accounts.market;
+        let now = current_timestamp()?;
 
-        require!(market.is_resolved, ErrorCode::MarketNotResolved);
-        require!(!bet.is_claimed, ErrorCode::AlreadyClaimed);
-        require!(
-            bet.outcome == market.winning_outcome.unwrap(),
-            ErrorCode::NotWinner
-        );
+        require!(amount > 0, ErrorCode::ZeroAmount);
 
-        // Verify ZK proof of ownership
-        verify_zk_proof(&proof, &bet.nullifier, &ctx.accounts.claimant.key())?;
+        // The creator's one-time seed bet is fee-free and may land before public betting opens
+        let is_creator_seed = market.creator_seed_fee_exempt
+            && !market.creator_seed_used
+            && ctx.accounts.bettor.key() == market.creator
+            && now < market.opening_time;
+        validate_bet_window(market, amount, now, is_creator_seed)?;
 
-        let total_pool = market.total_yes_amount + market.total_no_amount;
-        let winning_pool = match market.winning_outcome.unwrap() {
-            Outcome::Yes => market.total_yes_amount,
-            Outcome::No => market.total_no_amount,
-        };
+        let nullifier = admit_bettor(
+            vault,
+            market,
+            &ctx.accounts.bettor.key(),
+            &proof,
+            nullifier,
+            &nullifier_secret,
+            ctx.accounts.nullifier_account.as_mut(),
+            ctx.bumps.nullifier_account,
+        )?;
 
-        // Calculate winnings
-        let winnings = (bet.amount as u128 * total_pool as u128 / winning_pool as u128) as u64;
+        if is_creator_seed {
+            ctx.accounts.market.creator_seed_used = true;
+        }
 
-        // Transfer winnings
-        let seeds = &[
-            b"vault".as_ref(),
-            &ctx.accounts.vault.key().to_bytes(),
-            &[ctx.accounts.vault.nonce],
-        ];
-        let signer_seeds = &[&seeds[..]];
+        book_bet(
+            BetAccounts {
+                vault: &mut ctx.accounts.vault,
+                market: &mut ctx.accounts.market,
+                bet_account: &mut ctx.accounts.bet_account,
+                bettor: &ctx.accounts.bettor,
+                bettor_token_account: &ctx.accounts.bettor_token_account,
+                vault_token_account: &ctx.accounts.vault_token_account,
+                token_program: &ctx.accounts.token_program,
+            },
+            amount,
+            outcome_index,
+            nullifier,
+            memo,
+            is_creator_seed,
+            now,
+        )?;
 
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.vault_token_account.to_account_info(),
-            to: ctx.accounts.claimant_token_account.to_account_info(),
-            authority: ctx.accounts.vault.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-        token::transfer(cpi_ctx, winnings)?;
+        log_compute_units("place_bet:end");
 
-        bet.is_claimed = true;
-        bet.claimed_amount = winnings;
-        bet.claimed_timestamp = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
 
-        emit!(WinningsClaimed {
+    /// Phase one of a two-transaction bet for deep allowlists: verify the merkle proof
+    /// and reserve the nullifier so the bet itself can be placed without the proof
+    pub fn verify_and_reserve(
+        ctx: Context<VerifyAndReserve>,
+        proof: Vec<u8>,
+        nullifier: [u8; 32],
+        nullifier_secret: [u8; 32],
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let market = &ctx.accounts.market;
+        let now = current_timestamp()?;
+
+        require_feature(vault, FEATURE_BET_RESERVATIONS)?;
+        require!(market.vault == vault.key(), ErrorCode::InvalidMarketVault);
+        require_inflows_open(vault)?;
+        require!(!market.is_resolved, ErrorCode::MarketResolved);
+        require!(now < market.resolution_time, ErrorCode::MarketClosed);
+
+        require_allowlisted(vault, market, &ctx.accounts.bettor.key(), &proof)?;
+
+        require!(
+            nullifier == derive_nullifier(&nullifier_secret, &market.id),
+            ErrorCode::InvalidNullifier
+        );
+        require!(
+            !ctx.accounts.nullifier_account.is_used
+                && ctx.accounts.nullifier_account.nullifier == [0u8; 32],
+            ErrorCode::NullifierAlreadyUsed
+        );
+        ctx.accounts.nullifier_account.is_used = true;
+        ctx.accounts.nullifier_account.nullifier = nullifier;
+        ctx.accounts.nullifier_account.bump = ctx.bumps.nullifier_account;
+
+        let reservation = &mut ctx.accounts.reservation;
+        reservation.market = market.key();
+        reservation.bettor = ctx.accounts.bettor.key();
+        reservation.nullifier = nullifier;
+        reservation.is_consumed = false;
+
+        emit!(BetReserved {
             market: market.key(),
-            claimant: ctx.accounts.claimant.key(),
-            amount: winnings,
-            timestamp: Clock::get()?.unix_timestamp,
+            bettor: reservation.bettor,
+            reservation: reservation.key(),
+            nullifier,
+            timestamp: now,
         });
 
         Ok(())
     }
 
-    /// Add liquidity with LP token minting
-    pub fn add_liquidity(
-        ctx: Context<AddLiquidity>,
+    /// Phase two: place a bet on the outcome at `outcome_index` against a reservation
+    /// without re-verifying the proof
+    pub fn place_bet_reserved(
+        ctx: Context<PlaceBetReserved>,
         amount: u64,
+        outcome_index: u8,
+        memo: [u8; 16],
     ) -> Result<()> {
-        let market = &mut ctx.accounts.market;
-        let vault = &ctx.accounts.vault;
-        
-        // Calculate LP tokens to mint using constant product formula
-        let lp_tokens = calculate_lp_tokens(
-            amount,
-            market.liquidity_locked,
-            ctx.accounts.lp_token_supply.amount,
+        let market = &ctx.accounts.market;
+        let reservation = &mut ctx.accounts.reservation;
+        let now = current_timestamp()?;
+
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        validate_bet_window(market, amount, now, false)?;
+
+        require!(reservation.market == market.key(), ErrorCode::InvalidReservation);
+        require!(
+            reservation.bettor == ctx.accounts.bettor.key(),
+            ErrorCode::InvalidReservation
         );
+        require!(!reservation.is_consumed, ErrorCode::ReservationConsumed);
+        reservation.is_consumed = true;
+        let nullifier = reservation.nullifier;
 
-        // Transfer tokens to vault
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.provider_token_account.to_account_info(),
-            to: ctx.accounts.vault_token_account.to_account_info(),
-            authority: ctx.accounts.provider.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+        book_bet(
+            BetAccounts {
+                vault: &mut ctx.accounts.vault,
+                market: &mut ctx.accounts.market,
+                bet_account: &mut ctx.accounts.bet_account,
+                bettor: &ctx.accounts.bettor,
+                bettor_token_account: &ctx.accounts.bettor_token_account,
+                vault_token_account: &ctx.accounts.vault_token_account,
+                token_program: &ctx.accounts.token_program,
+            },
             amount,
-        )?;
+            outcome_index,
+            nullifier,
+            memo,
+            false,
+            now,
+        )
+    }
+
+    /// Push a late market's resolution time forward by the vault's extension window,
+    /// a bounded number of times and never past `max_total_extension_seconds` in total,
+    /// instead of voiding it
+    pub fn extend_resolution(ctx: Context<ExtendResolution>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let market = &mut ctx.accounts.market;
+        let signer = ctx.accounts.signer.key();
 
-        // Mint LP tokens
-        // Implementation would involve CPI to token program
+        require!(market.vault == vault.key(), ErrorCode::InvalidMarketVault);
+        require!(
+            signer == market.oracle || signer == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require_not_settled(market)?;
+        require!(
+            vault.resolution_extension_seconds > 0,
+            ErrorCode::ExtensionsDisabled
+        );
+        require!(
+            market.extension_count < vault.max_resolution_extensions,
+            ErrorCode::ExtensionLimitReached
+        );
+        let total_extension_seconds = market
+            .total_extension_seconds
+            .checked_add(vault.resolution_extension_seconds)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(
+            total_extension_seconds <= vault.max_total_extension_seconds,
+            ErrorCode::ExtensionCeilingReached
+        );
 
-        market.liquidity_locked += amount;
+        let now = current_timestamp()?;
+        // Extending past the clock reopens a closed market
+        let from_phase = market_phase(market, now);
+        market.resolution_time =
+            checked_add_seconds(market.resolution_time, vault.resolution_extension_seconds)?;
+        market.extension_count += 1;
+        market.total_extension_seconds = total_extension_seconds;
 
-        emit!(LiquidityAdded {
+        emit!(ResolutionExtended {
             market: market.key(),
-            provider: ctx.accounts.provider.key(),
-            amount,
-            lp_tokens,
-            timestamp: Clock::get()?.unix_timestamp,
+            new_resolution_time: market.resolution_time,
+            extension_count: market.extension_count,
+            total_extension_seconds,
+            timestamp: now,
         });
+        emit_lifecycle_event(market, Some(from_phase), signer, now);
 
         Ok(())
     }
-}
 
-// ===== Helper Functions =====
+    /// Register a survey opinion for the vault's fixed `survey_stake`, once per voter per
+    /// market. Votes are tallied apart from the betting pools, so they never move the odds
+    /// or share in payouts; the stake is an anti-spam cost booked as fees.
+    pub fn survey_vote(ctx: Context<SurveyVote>, choice: SurveyChoice) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let market = &mut ctx.accounts.market;
+        let now = current_timestamp()?;
 
-fn calculate_initial_probability(yes_amount: u64, no_amount: u64) -> u64 {
-    if yes_amount == 0 && no_amount == 0 {
-        return 5000; // 50%
-    }
-    let total = yes_amount + no_amount;
-    (yes_amount as u128 * 10000 / total as u128) as u64
-}
+        require!(vault.survey_stake > 0, ErrorCode::SurveyDisabled);
+        require!(market.vault == vault.key(), ErrorCode::InvalidMarketVault);
+        require_inflows_open(vault)?;
+        require_vault_token_account(&ctx.accounts.vault_token_account, &vault.key())?;
+        require!(!market.is_resolved, ErrorCode::MarketResolved);
+        require!(now < market.resolution_time, ErrorCode::MarketClosed);
+        require_settlement_mint(
+            market,
+            &[
+                &ctx.accounts.voter_token_account,
+                &ctx.accounts.vault_token_account,
+            ],
+        )?;
 
-fn calculate_lmsr_probability(yes: u64, no: u64, liquidity: u64) -> u64 {
-    // Logarithmic Market Scoring Rule implementation
-    let b = liquidity.max(1) as f64;
-    let yes_f = yes as f64;
-    let no_f = no as f64;
-    
-    let exp_yes_b = (yes_f / b).exp();
-    let exp_no_b = (no_f / b).exp();
-    let probability = exp_yes_b / (exp_yes_b + exp_no_b);
-    
-    (probability * 10000.0) as u64
-}
-
-fn calculate_lp_tokens(amount: u64, locked: u64, supply: u64) -> u64 {
-    if supply == 0 {
-        amount // Initial liquidity
-    } else {
-        (amount as u128 * supply as u128 / locked as u128) as u64
+        let tally = match choice {
+            SurveyChoice::Yes => &mut market.survey_yes_votes,
+            SurveyChoice::No => &mut market.survey_no_votes,
+            SurveyChoice::Abstain => &mut market.survey_abstain_votes,
+        };
+        *tally = tally.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+
+        let ballot = &mut ctx.accounts.survey_ballot;
+        ballot.market = market.key();
+        ballot.voter = ctx.accounts.voter.key();
+        ballot.choice = choice;
+
+        let survey_stake = vault.survey_stake;
+        accrue_fees(vault, &market.settlement_mint, survey_stake)?;
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.voter_token_account.to_account_info(),
+            to: ctx.accounts.vault_token_account.to_account_info(),
+            authority: ctx.accounts.voter.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+            survey_stake,
+        )?;
+
+        emit!(SurveyVoted {
+            market: market.key(),
+            voter: ballot.voter,
+            choice,
+            stake: survey_stake,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Split an open position into two bet accounts of `amount_a` and the remainder
+    pub fn split_bet(ctx: Context<SplitBet>, amount_a: u64) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let market = &mut ctx.accounts.market;
+        let bet = &mut ctx.accounts.bet_account;
+        let now = current_timestamp()?;
+
+        require!(!market.is_resolved, ErrorCode::MarketResolved);
+        require!(now < market.resolution_time, ErrorCode::MarketClosed);
+        require!(bet.market == market.key(), ErrorCode::BetMarketMismatch);
+        require!(market.vault == vault.key(), ErrorCode::InvalidMarketVault);
+        require!(
+            bet.bettor == ctx.accounts.bettor.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(!bet.is_claimed, ErrorCode::AlreadyClaimed);
+        require!(
+            amount_a > 0 && amount_a < bet.amount,
+            ErrorCode::InvalidSplitAmount
+        );
+
+        // The bet-time fee follows the stake so a void refunds each half its own share
+        let fee_a = checked_mul_div(bet.fee_amount as u128, amount_a as u128, bet.amount as u128)?;
+        let tip_a = checked_mul_div(bet.keeper_tip as u128, amount_a as u128, bet.amount as u128)?;
+        // Outcome shares split with the stake, so both halves keep the bet's average price
+        let shares_a = checked_mul_div(bet.shares as u128, amount_a as u128, bet.amount as u128)?;
+        bet.amount = bet.amount.checked_sub(amount_a).ok_or(ErrorCode::MathOverflow)?;
+        bet.fee_amount = bet.fee_amount.checked_sub(fee_a).ok_or(ErrorCode::MathOverflow)?;
+        bet.keeper_tip = bet.keeper_tip.checked_sub(tip_a).ok_or(ErrorCode::MathOverflow)?;
+        bet.shares = bet.shares.checked_sub(shares_a).ok_or(ErrorCode::MathOverflow)?;
+
+        let new_bet = &mut ctx.accounts.new_bet_account;
+        require_pristine_bet(new_bet)?;
+        new_bet.market = bet.market;
+        new_bet.bettor = bet.bettor;
+        new_bet.amount = amount_a;
+        new_bet.outcome = bet.outcome;
+        new_bet.outcome_index = bet.outcome_index;
+        new_bet.timestamp = bet.timestamp;
+        new_bet.odds = bet.odds;
+        new_bet.nullifier = bet.nullifier;
+        new_bet.is_claimed = false;
+        new_bet.claimed_amount = 0;
+        new_bet.claimed_timestamp = 0;
+        new_bet.memo = bet.memo;
+        // The split-off stake was already counted in volume by the original bet
+        new_bet.vault = vault.key();
+        new_bet.sequence = vault.bet_count;
+        new_bet.volume_amount = 0;
+        new_bet.fee_amount = fee_a;
+        new_bet.keeper_tip = tip_a;
+        new_bet.fee_basis_points = bet.fee_basis_points;
+        new_bet.shares = shares_a;
+        new_bet.settlement_generation = bet.settlement_generation;
+        // Splits close at `resolution_time`, before voting opens
+        new_bet.resolution_voted = false;
+        new_bet.market_index = market.bet_count;
+        new_bet.ownership_transferred = bet.ownership_transferred;
+        vault.bet_count = vault.bet_count.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        market.bet_count = market.bet_count.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(BetSplit {
+            market: market.key(),
+            original_bet: bet.key(),
+            new_bet: new_bet.key(),
+            remaining_amount: bet.amount,
+            split_amount: amount_a,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Hand an open position to `new_owner`. A transfer moves claim ownership to the new
+    /// owner's signature: in a private market the nullifier's ZK proof stays with the seller,
+    /// who still knows the secret, so a transferred bet no longer accepts it.
+    pub fn transfer_position(ctx: Context<TransferPosition>, new_owner: Pubkey) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let bet = &mut ctx.accounts.bet_account;
+
+        require!(bet.market == market.key(), ErrorCode::BetMarketMismatch);
+        require_transferable(market, bet, &ctx.accounts.bettor.key(), &new_owner)?;
+
+        let previous_owner = bet.bettor;
+        bet.bettor = new_owner;
+        bet.ownership_transferred = true;
+
+        emit!(PositionTransferred {
+            market: market.key(),
+            bet: bet.key(),
+            previous_owner,
+            new_owner,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Resolve market with oracle verification. `winning_index` names one of the market's
+    /// outcomes; in a Yes/No market it is the `Outcome` discriminant, which encodes identically.
+    pub fn resolve_market(
+        ctx: Context<ResolveMarket>,
+        winning_index: u8,
+        oracle_signature: Vec<u8>,
+        reveal_value: [u8; 32],
+        source_ref: [u8; 32],
+    ) -> Result<()> {
+        log_compute_units("resolve_market:start");
+        let market = &mut ctx.accounts.market;
+        let now = current_timestamp()?;
+
+        // Keeper retries: an oracle-signed resubmission of the recorded outcome is a no-op,
+        // while any other outcome still hits the settled-market guard below
+        if market.is_resolved
+            && is_oracle_submitter(market, &ctx.accounts.oracle.key())
+            && market.winning_index == Some(winning_index)
+        {
+            verify_oracle_signature(
+                &oracle_signature,
+                market,
+                winning_index,
+                &source_ref,
+                &ctx.accounts.instructions,
+            )
+            .map_err(|e| log_verification_failure("oracle_signature", &market.key(), e))?;
+            return Ok(());
+        }
+
+        require_not_settled(market)?;
+        require!(market.vault == ctx.accounts.vault.key(), ErrorCode::InvalidMarketVault);
+        require!(!market.community_resolved, ErrorCode::CommunityResolvedMarket);
+        require!(
+            now >= market.resolution_time || market.allow_early_resolution,
+            ErrorCode::TooEarlyToResolve
+        );
+        require!(
+            is_oracle_submitter(market, &ctx.accounts.oracle.key()),
+            ErrorCode::UnauthorizedOracle
+        );
+        require_valid_outcome(market_pools(market), winning_index)?;
+        require_settlement_mint(market, &[&ctx.accounts.vault_token_account])?;
+
+        verify_reveal(market, &reveal_value)?;
+
+        // Verify oracle signature
+        verify_oracle_signature(
+            &oracle_signature,
+            market,
+            winning_index,
+            &source_ref,
+            &ctx.accounts.instructions,
+        )
+        .map_err(|e| log_verification_failure("oracle_signature", &market.key(), e))?;
+
+        let from_phase = market_phase(market, now);
+        close_betting_early(market, now);
+        market.is_resolved = true;
+        market.winning_index = Some(winning_index);
+        market.winning_outcome = binary_outcome(market, winning_index)?;
+        market.resolution_timestamp = now;
+        market.source_ref = source_ref;
+        market.claims_unlock_time =
+            checked_add_seconds(now, ctx.accounts.vault.authority_override_window_seconds)?;
+        market.resolution_finalizes_at =
+            checked_add_seconds(now, ctx.accounts.vault.challenge_window_seconds)?;
+        snapshot_lp_entitlements(market);
+        bump_settlement_generation(market)?;
+        release_creator_slot(market, ctx.accounts.creator_stats.as_mut())?;
+
+        // Display-only USD reference for the settlement token; never used in payout math
+        let (reference_price, reference_expo) = match market.price_reference_account {
+            Some(expected) => {
+                let price_account = ctx
+                    .accounts
+                    .price_reference
+                    .as_ref()
+                    .ok_or(ErrorCode::InvalidPriceReference)?;
+                require!(
+                    price_account.key() == expected,
+                    ErrorCode::InvalidPriceReference
+                );
+                read_reference_price(price_account, now)?
+            }
+            None => (0, 0),
+        };
+
+        // Burn the configured share of the losing pool; a refund (no winners) burns nothing
+        let pools = market_pools(market).to_vec();
+        let winner = winning_index as usize;
+        let burned = calculate_resolution_burn(&pools, winner, market.burn_basis_points)?;
+        if burned > 0 {
+            require!(
+                ctx.accounts.settlement_mint.key() == market.settlement_mint
+                    && ctx.accounts.vault_token_account.owner == ctx.accounts.vault.key(),
+                ErrorCode::InvalidBurnAccount
+            );
+            require!(
+                ctx.accounts.vault_token_account.amount >= burned,
+                ErrorCode::InsufficientVaultBalance
+            );
+            burn_from_vault(
+                &ctx.accounts.vault,
+                &ctx.accounts.settlement_mint,
+                &ctx.accounts.vault_token_account,
+                &ctx.accounts.token_program,
+                burned,
+            )?;
+            emit!(LoserPoolBurned {
+                market: market.key(),
+                amount: burned,
+                timestamp: now,
+            });
+        }
+        market.burned_amount = burned;
+
+        let keeper_tip = pay_keeper_pool(
+            market,
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.resolver_token_account,
+            &ctx.accounts.oracle.key(),
+            &ctx.accounts.token_program,
+            now,
+        )?;
+
+        // Record the distributable pool; claims can never exceed it
+        open_claims(
+            market,
+            pooled_stakes(market)?
+                .checked_add(market.insurance_pool)
+                .and_then(|pool| pool.checked_sub(burned))
+                .ok_or(ErrorCode::MathOverflow)?,
+            ctx.accounts
+                .vault_token_account
+                .amount
+                .checked_sub(burned)
+                .and_then(|balance| balance.checked_sub(keeper_tip))
+                .ok_or(ErrorCode::MathOverflow)?,
+            ctx.accounts.vault.claim_window_seconds,
+            now,
+        )?;
+
+        // Nobody backed the winner: every stake is refunded and no ratio applies
+        let is_void = pools[winner] == 0;
+        let winning_payout_ratio = if is_void {
+            0
+        } else {
+            calculate_winning_payout_ratio(&pools, winner, burned)?
+        };
+
+        emit!(MarketResolved {
+            market: market.key(),
+            winning_index,
+            total_yes: market.total_yes_amount,
+            total_no: market.total_no_amount,
+            outcome_totals: pools,
+            winning_payout_ratio,
+            is_void,
+            source_ref,
+            reference_price,
+            reference_expo,
+            timestamp: now,
+        });
+        emit_lifecycle_event(market, Some(from_phase), ctx.accounts.oracle.key(), now);
+
+        emit_survey_results(market, now);
+
+        log_compute_units("resolve_market:end");
+
+        Ok(())
+    }
+
+    /// Resolve a probabilistic market by splitting the pool between Yes and No holders
+    /// in proportion to the oracle-reported `yes_probability` (basis points) rather than
+    /// winner-take-all. Each side's share is distributed pro-rata among that side's stakes;
+    /// a side with no stake forfeits its share to the other side.
+    pub fn resolve_market_probabilistic(
+        ctx: Context<ResolveMarket>,
+        yes_probability: u64,
+        oracle_signature: Vec<u8>,
+        reveal_value: [u8; 32],
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let now = current_timestamp()?;
+
+        require!(market.is_probabilistic, ErrorCode::NotProbabilisticMarket);
+        require_not_settled(market)?;
+        require!(market.vault == ctx.accounts.vault.key(), ErrorCode::InvalidMarketVault);
+        require!(
+            now >= market.resolution_time || market.allow_early_resolution,
+            ErrorCode::TooEarlyToResolve
+        );
+        require!(
+            is_oracle_submitter(market, &ctx.accounts.oracle.key()),
+            ErrorCode::UnauthorizedOracle
+        );
+        require!(yes_probability <= 10000, ErrorCode::InvalidProbability);
+        require_settlement_mint(market, &[&ctx.accounts.vault_token_account])?;
+
+        verify_reveal(market, &reveal_value)?;
+
+        // Verify oracle signature over the reported probability
+        verify_oracle_message(
+            &oracle_signature,
+            &oracle_probability_message(&market.id, yes_probability),
+            &market.oracle,
+            market.oracle_signature_scheme,
+            &ctx.accounts.instructions,
+        )
+        .map_err(|e| log_verification_failure("oracle_signature", &market.key(), e))?;
+
+        let from_phase = market_phase(market, now);
+        close_betting_early(market, now);
+        market.is_resolved = true;
+        market.resolved_yes_probability = yes_probability;
+        market.resolution_timestamp = now;
+        snapshot_lp_entitlements(market);
+        bump_settlement_generation(market)?;
+        release_creator_slot(market, ctx.accounts.creator_stats.as_mut())?;
+
+        let keeper_tip = pay_keeper_pool(
+            market,
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.resolver_token_account,
+            &ctx.accounts.oracle.key(),
+            &ctx.accounts.token_program,
+            now,
+        )?;
+
+        open_claims(
+            market,
+            pooled_stakes(market)?,
+            ctx.accounts
+                .vault_token_account
+                .amount
+                .checked_sub(keeper_tip)
+                .ok_or(ErrorCode::MathOverflow)?,
+            ctx.accounts.vault.claim_window_seconds,
+            now,
+        )?;
+
+        emit_survey_results(market, now);
+
+        emit!(MarketResolvedProbabilistic {
+            market: market.key(),
+            yes_probability,
+            total_yes: market.total_yes_amount,
+            total_no: market.total_no_amount,
+            timestamp: now,
+        });
+        emit_lifecycle_event(market, Some(from_phase), ctx.accounts.oracle.key(), now);
+
+        Ok(())
+    }
+
+    /// Correct an oracle-resolved market's winning outcome while its claims are still locked
+    /// by the vault's override window. The settlement generation is kept: nothing has been
+    /// paid, so every bet stays claimable under the corrected outcome. Markets that burned
+    /// part of the losing pool cannot be overridden, since the burn followed the old outcome.
+    pub fn override_resolution(
+        ctx: Context<OverrideResolution>,
+        corrected_outcome: Outcome,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let market = &mut ctx.accounts.market;
+        let now = current_timestamp()?;
+
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(market.vault == vault.key(), ErrorCode::InvalidMarketVault);
+        require!(market.is_resolved, ErrorCode::MarketNotResolved);
+        require!(
+            market.outcome_count == 2
+                && !market.is_probabilistic
+                && !market.is_void
+                && !market.community_resolved,
+            ErrorCode::OverrideNotAllowed
+        );
+        require!(now < market.claims_unlock_time, ErrorCode::OverrideWindowClosed);
+        require!(market.burned_amount == 0, ErrorCode::OverrideNotAllowed);
+        let previous_outcome = market.winning_outcome.ok_or(ErrorCode::MarketNotResolved)?;
+        require!(corrected_outcome != previous_outcome, ErrorCode::InvalidOutcome);
+        require_valid_outcome(market_pools(market), corrected_outcome.discriminant())?;
+
+        market.winning_outcome = Some(corrected_outcome);
+        market.winning_index = Some(corrected_outcome.discriminant());
+        // Any entitlement tree was built against the old outcome
+        reset_settlement_progress(market);
+
+        emit!(ResolutionOverridden {
+            market: market.key(),
+            previous_outcome,
+            corrected_outcome,
+            authority: ctx.accounts.authority.key(),
+            claims_unlock_time: market.claims_unlock_time,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Challenge an oracle resolution within its challenge window by posting the vault's
+    /// dispute bond. Any bettor of the market may dispute; claims stay frozen until the
+    /// authority rules on it with `resolve_dispute`. Probabilistic and community-resolved
+    /// markets have no outcome the authority could correct, so they cannot be disputed.
+    pub fn raise_dispute(ctx: Context<RaiseDispute>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let market = &mut ctx.accounts.market;
+        let bet = &ctx.accounts.bet_account;
+        let now = current_timestamp()?;
+
+        require!(market.vault == vault.key(), ErrorCode::InvalidMarketVault);
+        require_disputable(market, now)?;
+        require!(bet.market == market.key(), ErrorCode::BetMarketMismatch);
+        require!(
+            bet.bettor == ctx.accounts.disputer.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(vault.dispute_bond_amount > 0, ErrorCode::DisputesDisabled);
+        require_vault_token_account(&ctx.accounts.vault_token_account, &vault.key())?;
+        require_settlement_mint(
+            market,
+            &[
+                &ctx.accounts.disputer_token_account,
+                &ctx.accounts.vault_token_account,
+            ],
+        )?;
+
+        let bond = receive_into_vault(
+            &ctx.accounts.disputer_token_account,
+            &ctx.accounts.vault_token_account,
+            ctx.accounts.disputer.to_account_info(),
+            &ctx.accounts.token_program,
+            vault.dispute_bond_amount,
+        )?;
+        market.is_disputed = true;
+        market.disputer = ctx.accounts.disputer.key();
+        market.dispute_bond = bond;
+
+        emit!(DisputeRaised {
+            market: market.key(),
+            disputer: market.disputer,
+            bond,
+            winning_index: market.winning_index.ok_or(ErrorCode::MarketNotResolved)?,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Rule on a disputed market. Passing `corrected_index` upholds the dispute: the winning
+    /// outcome is overridden under the same rules as `override_resolution` and the bond is
+    /// refunded. Passing `None` confirms the resolution and slashes the bond into fees.
+    /// Either way claims open immediately, with any claim window restarted from now.
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDispute>,
+        corrected_index: Option<u8>,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let market = &mut ctx.accounts.market;
+        let now = current_timestamp()?;
+
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(market.vault == vault.key(), ErrorCode::InvalidMarketVault);
+        require!(market.is_disputed, ErrorCode::MarketNotDisputed);
+
+        let previous_index = market.winning_index.ok_or(ErrorCode::MarketNotResolved)?;
+        let bond = market.dispute_bond;
+        match corrected_index {
+            Some(corrected_index) => {
+                require!(
+                    !market.is_probabilistic
+                        && !market.is_void
+                        && !market.community_resolved
+                        && market.burned_amount == 0,
+                    ErrorCode::OverrideNotAllowed
+                );
+                require!(corrected_index != previous_index, ErrorCode::InvalidOutcome);
+                require_valid_outcome(market_pools(market), corrected_index)?;
+
+                market.winning_index = Some(corrected_index);
+                market.winning_outcome = binary_outcome(market, corrected_index)?;
+                // Any entitlement tree was built against the old outcome
+                reset_settlement_progress(market);
+
+                if bond > 0 {
+                    let disputer_token_account = ctx
+                        .accounts
+                        .disputer_token_account
+                        .as_ref()
+                        .ok_or(ErrorCode::InvalidDisputerAccount)?;
+                    require!(
+                        disputer_token_account.owner == market.disputer,
+                        ErrorCode::InvalidDisputerAccount
+                    );
+                    require_vault_token_account(&ctx.accounts.vault_token_account, &vault.key())?;
+                    require_settlement_mint(
+                        market,
+                        &[disputer_token_account, &ctx.accounts.vault_token_account],
+                    )?;
+                    transfer_from_vault(
+                        vault,
+                        &ctx.accounts.vault_token_account,
+                        disputer_token_account,
+                        &ctx.accounts.token_program,
+                        bond,
+                    )?;
+                }
+            }
+            None => accrue_fees(vault, &market.settlement_mint, bond)?,
+        }
+
+        market.is_disputed = false;
+        market.dispute_bond = 0;
+        market.resolution_finalizes_at = now;
+        if market.claim_deadline != 0 {
+            market.claim_deadline = market
+                .claim_deadline
+                .max(checked_add_seconds(now, vault.claim_window_seconds)?);
+        }
+
+        emit!(DisputeResolved {
+            market: market.key(),
+            authority: ctx.accounts.authority.key(),
+            disputer: market.disputer,
+            upheld: corrected_index.is_some(),
+            previous_index,
+            winning_index: market.winning_index.ok_or(ErrorCode::MarketNotResolved)?,
+            bond,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Cast a community-resolved market's resolution vote with one bet's stake as weight.
+    /// Voting opens at `resolution_time`; the vote that brings turnout to the market's quorum
+    /// with a strict majority on one outcome resolves the market to it.
+    pub fn submit_resolution_vote(
+        ctx: Context<SubmitResolutionVote>,
+        outcome_index: u8,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let bet = &mut ctx.accounts.bet_account;
+        let now = current_timestamp()?;
+
+        require!(market.community_resolved, ErrorCode::NotCommunityResolved);
+        require!(market.vault == ctx.accounts.vault.key(), ErrorCode::InvalidMarketVault);
+        require_not_settled(market)?;
+        require!(
+            now >= market.resolution_time,
+            ErrorCode::TooEarlyToResolve
+        );
+        require!(
+            now < community_vote_deadline(market)?,
+            ErrorCode::ResolutionVotingClosed
+        );
+        require!(bet.market == market.key(), ErrorCode::BetMarketMismatch);
+        require!(
+            bet.bettor == ctx.accounts.bettor.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(!bet.resolution_voted, ErrorCode::AlreadyVoted);
+        require_valid_outcome(market_pools(market), outcome_index)?;
+
+        let tally = &mut market.resolution_votes[outcome_index as usize];
+        *tally = tally.checked_add(bet.amount).ok_or(ErrorCode::MathOverflow)?;
+        bet.resolution_voted = true;
+
+        emit!(ResolutionVoteSubmitted {
+            market: market.key(),
+            bettor: bet.bettor,
+            outcome_index,
+            weight: bet.amount,
+            timestamp: now,
+        });
+
+        let Some(winning_index) = community_vote_result(market) else {
+            return Ok(());
+        };
+
+        require_settlement_mint(market, &[&ctx.accounts.vault_token_account])?;
+        let from_phase = market_phase(market, now);
+        market.is_resolved = true;
+        market.winning_index = Some(winning_index);
+        market.winning_outcome = binary_outcome(market, winning_index)?;
+        market.resolution_timestamp = now;
+        snapshot_lp_entitlements(market);
+        bump_settlement_generation(market)?;
+        release_creator_slot(market, ctx.accounts.creator_stats.as_mut())?;
+        open_claims(
+            market,
+            pooled_stakes(market)?,
+            ctx.accounts.vault_token_account.amount,
+            ctx.accounts.vault.claim_window_seconds,
+            now,
+        )?;
+
+        let pools = market_pools(market).to_vec();
+        let winner = winning_index as usize;
+        let is_void = pools[winner] == 0;
+        let winning_payout_ratio = if is_void {
+            0
+        } else {
+            calculate_winning_payout_ratio(&pools, winner, 0)?
+        };
+
+        emit!(MarketResolved {
+            market: market.key(),
+            winning_index,
+            total_yes: market.total_yes_amount,
+            total_no: market.total_no_amount,
+            outcome_totals: pools.to_vec(),
+            winning_payout_ratio,
+            is_void,
+            source_ref: [0u8; 32],
+            reference_price: 0,
+            reference_expo: 0,
+            timestamp: now,
+        });
+        emit_lifecycle_event(market, Some(from_phase), bet.bettor, now);
+
+        emit_survey_results(market, now);
+
+        Ok(())
+    }
+
+    /// Void a community-resolved market whose vote window closed without reaching quorum
+    /// and a majority, refunding every stake. Callable by anyone.
+    pub fn void_community_market(ctx: Context<VoidCommunityMarket>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let now = current_timestamp()?;
+
+        require!(market.community_resolved, ErrorCode::NotCommunityResolved);
+        require!(market.vault == ctx.accounts.vault.key(), ErrorCode::InvalidMarketVault);
+        require_not_settled(market)?;
+        require!(
+            now >= community_vote_deadline(market)?,
+            ErrorCode::ResolutionVotingOpen
+        );
+        require_settlement_mint(market, &[&ctx.accounts.vault_token_account])?;
+
+        let from_phase = market_phase(market, now);
+        market.is_resolved = true;
+        market.is_void = true;
+        market.winning_outcome = None;
+        market.winning_index = None;
+        market.resolution_timestamp = now;
+        snapshot_lp_entitlements(market);
+        bump_settlement_generation(market)?;
+        release_creator_slot(market, ctx.accounts.creator_stats.as_mut())?;
+        open_claims(
+            market,
+            pooled_stakes(market)?,
+            ctx.accounts.vault_token_account.amount,
+            ctx.accounts.vault.claim_window_seconds,
+            now,
+        )?;
+
+        emit!(MarketVoided {
+            market: market.key(),
+            votes: market.resolution_votes[..market.outcome_count as usize].to_vec(),
+            total_refundable: market.total_claimable,
+            timestamp: now,
+        });
+        emit_lifecycle_event(market, Some(from_phase), ctx.accounts.caller.key(), now);
+
+        Ok(())
+    }
+
+    /// Cancel a market its oracle never resolved. Once `resolution_time` plus the vault's
+    /// cancellation grace has passed, the vault authority or the market creator may void it
+    /// so every bettor can reclaim their stake through `claim_winnings`.
+    pub fn cancel_market(ctx: Context<CancelMarket>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let vault = &ctx.accounts.vault;
+        let now = current_timestamp()?;
+        let caller = ctx.accounts.caller.key();
+
+        require!(
+            caller == vault.authority || caller == market.creator,
+            ErrorCode::Unauthorized
+        );
+        require!(market.vault == vault.key(), ErrorCode::InvalidMarketVault);
+        require_not_settled(market)?;
+        require!(
+            now >= checked_add_seconds(market.resolution_time, vault.cancellation_grace_seconds)?,
+            ErrorCode::CancellationGraceActive
+        );
+        require_settlement_mint(market, &[&ctx.accounts.vault_token_account])?;
+
+        let from_phase = market_phase(market, now);
+        market.is_resolved = true;
+        market.is_void = true;
+        market.is_cancelled = true;
+        market.winning_outcome = None;
+        market.winning_index = None;
+        market.resolution_timestamp = now;
+        snapshot_lp_entitlements(market);
+        bump_settlement_generation(market)?;
+        release_creator_slot(market, ctx.accounts.creator_stats.as_mut())?;
+        open_claims(
+            market,
+            pooled_stakes(market)?,
+            ctx.accounts.vault_token_account.amount,
+            ctx.accounts.vault.claim_window_seconds,
+            now,
+        )?;
+
+        emit!(MarketCancelled {
+            market: market.key(),
+            cancelled_by: caller,
+            total_refundable: market.total_claimable,
+            timestamp: now,
+        });
+        emit_lifecycle_event(market, Some(from_phase), caller, now);
+
+        Ok(())
+    }
+
+    /// Set how long past `resolution_time` an unresolved market must wait before
+    /// `cancel_market` may void it
+    pub fn set_cancellation_grace(
+        ctx: Context<SetCancellationGrace>,
+        cancellation_grace_seconds: u64,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        vault.cancellation_grace_seconds = cancellation_grace_seconds;
+        require_lockup_horizon(vault)?;
+
+        emit!(CancellationGraceUpdated {
+            vault: vault.key(),
+            cancellation_grace_seconds,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
     }
+
+    /// Set how long claims stay open once they unlock, for markets settled from now on.
+    /// Zero lets claims stay open indefinitely.
+    pub fn set_claim_window(ctx: Context<SetClaimWindow>, claim_window_seconds: u64) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        vault.claim_window_seconds = claim_window_seconds;
+        require_lockup_horizon(vault)?;
+
+        emit!(ClaimWindowUpdated {
+            vault: vault.key(),
+            claim_window_seconds,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Configure how long oracle resolutions stay open to dispute, for markets resolved from
+    /// now on, and the bond a dispute must post. A zero bond disables disputes.
+    pub fn set_dispute_config(
+        ctx: Context<SetDisputeConfig>,
+        challenge_window_seconds: u64,
+        dispute_bond_amount: u64,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        vault.challenge_window_seconds = challenge_window_seconds;
+        vault.dispute_bond_amount = dispute_bond_amount;
+        require_lockup_horizon(vault)?;
+
+        emit!(DisputeConfigUpdated {
+            vault: vault.key(),
+            challenge_window_seconds,
+            dispute_bond_amount,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// After a market's claim deadline, move whatever its winners left unclaimed to a
+    /// treasury account owned by the authority. Only the market's own unclaimed remainder
+    /// is moved, and claims are already rejected by then, so nothing still owed is touched.
+    pub fn sweep_unclaimed(ctx: Context<SweepUnclaimed>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let market = &mut ctx.accounts.market;
+        let now = current_timestamp()?;
+
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(market.vault == vault.key(), ErrorCode::InvalidMarketVault);
+        require!(market.is_resolved, ErrorCode::MarketNotResolved);
+        require!(
+            market.claim_deadline != 0 && now >= market.claim_deadline,
+            ErrorCode::ClaimWindowOpen
+        );
+        require_vault_token_account(&ctx.accounts.vault_token_account, &vault.key())?;
+        require_settlement_mint(
+            market,
+            &[
+                &ctx.accounts.vault_token_account,
+                &ctx.accounts.treasury_token_account,
+            ],
+        )?;
+        require!(
+            ctx.accounts.treasury_token_account.owner == vault.authority,
+            ErrorCode::InvalidTreasuryAccount
+        );
+
+        let unclaimed = unclaimed_balance(market);
+        // Keeper tips of a voided market that were never refunded are unclaimed too
+        let unpaid_tips = if market.is_void { market.keeper_pool } else { 0 };
+        let amount = unclaimed.checked_add(unpaid_tips).ok_or(ErrorCode::MathOverflow)?;
+        require!(amount > 0, ErrorCode::NothingToSweep);
+
+        transfer_from_vault(
+            vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.treasury_token_account,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+
+        // Book the sweep as claimed so the remainder can never be paid out or swept twice
+        market.total_claimed = market
+            .total_claimed
+            .checked_add(unclaimed)
+            .ok_or(ErrorCode::MathOverflow)?;
+        market.keeper_pool = market
+            .keeper_pool
+            .checked_sub(unpaid_tips)
+            .ok_or(ErrorCode::MathOverflow)?;
+        market.unclaimed_swept = amount;
+
+        emit!(UnclaimedSwept {
+            market: market.key(),
+            treasury: ctx.accounts.treasury_token_account.key(),
+            amount,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Set the vote quorum (share of total stake) and vote window for community-resolved
+    /// markets created from now on
+    pub fn set_community_resolution_policy(
+        ctx: Context<SetCommunityResolutionPolicy>,
+        community_quorum_basis_points: u16,
+        community_vote_window_seconds: u64,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            community_quorum_basis_points > 0 && community_quorum_basis_points <= 10_000,
+            ErrorCode::InvalidCommunityMarket
+        );
+        require!(
+            community_vote_window_seconds > 0,
+            ErrorCode::InvalidCommunityMarket
+        );
+
+        vault.community_quorum_basis_points = community_quorum_basis_points;
+        vault.community_vote_window_seconds = community_vote_window_seconds;
+        require_lockup_horizon(vault)?;
+
+        emit!(CommunityResolutionPolicyUpdated {
+            vault: vault.key(),
+            community_quorum_basis_points,
+            community_vote_window_seconds,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Claim winnings with zero-knowledge proof
+    pub fn claim_winnings(
+        ctx: Context<ClaimWinnings>,
+        proof: Vec<u8>,
+    ) -> Result<()> {
+        log_compute_units("claim_winnings:start");
+        let bet = &mut ctx.accounts.bet_account;
+        let market = &mut ctx.accounts.market;
+
+        require_settlement_mint(
+            market,
+            &[
+                &ctx.accounts.vault_token_account,
+                &ctx.accounts.claimant_token_account,
+            ],
+        )?;
+        require!(market.vault == ctx.accounts.vault.key(), ErrorCode::InvalidMarketVault);
+        require!(bet.market == market.key(), ErrorCode::BetMarketMismatch);
+        let gross_winnings = settle_claim(market, bet, &ctx.accounts.claimant.key(), &proof)?;
+        let fee_taken =
+            take_winnings_fee(&mut ctx.accounts.vault, market, gross_winnings, bet.amount)?;
+        let fee_refunded = refund_bet_fee(&mut ctx.accounts.vault, market, bet)?;
+        let winnings = net_winnings(gross_winnings, fee_taken, fee_refunded)?;
+
+        // Transfer winnings
+        transfer_from_vault(
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.claimant_token_account,
+            &ctx.accounts.token_program,
+            winnings,
+        )?;
+
+        bet.is_claimed = true;
+        bet.claimed_amount = winnings;
+        bet.claimed_timestamp = current_timestamp()?;
+
+        emit!(WinningsClaimed {
+            market: market.key(),
+            claimant: ctx.accounts.claimant.key(),
+            amount: winnings,
+            gross_winnings,
+            fee_taken,
+            fee_refunded,
+            original_stake: bet.amount,
+            is_refund: is_no_contest(market),
+            mint_decimals: market.mint_decimals,
+            timestamp: current_timestamp()?,
+        });
+        emit_vault_balance_snapshot(
+            market,
+            &ctx.accounts.vault_token_account,
+            current_timestamp()?,
+        )?;
+
+        log_compute_units("claim_winnings:end");
+
+        Ok(())
+    }
+
+    /// Claim a settled bet and roll `amount` of the payout into a bet on another open market
+    /// of the same vault in one transaction. The payout lands in the claimant's token
+    /// account and the new bet is drawn from it with every `place_bet` check applied; any
+    /// remainder stays with the claimant.
+    pub fn claim_and_bet(
+        ctx: Context<ClaimAndBet>,
+        amount: u64,
+        outcome_index: u8,
+        claim_proof: Vec<u8>,
+        allowlist_proof: Vec<u8>,
+        nullifier: [u8; 32],
+        nullifier_secret: [u8; 32],
+        memo: [u8; 16],
+    ) -> Result<()> {
+        let now = current_timestamp()?;
+        require!(amount > 0, ErrorCode::ZeroAmount);
+
+        let bet = &mut ctx.accounts.bet_account;
+        let market = &mut ctx.accounts.market;
+        require_settlement_mint(
+            market,
+            &[
+                &ctx.accounts.vault_token_account,
+                &ctx.accounts.claimant_token_account,
+            ],
+        )?;
+        require!(market.vault == ctx.accounts.vault.key(), ErrorCode::InvalidMarketVault);
+        require!(bet.market == market.key(), ErrorCode::BetMarketMismatch);
+        let gross_winnings = settle_claim(market, bet, &ctx.accounts.claimant.key(), &claim_proof)?;
+        let fee_taken =
+            take_winnings_fee(&mut ctx.accounts.vault, market, gross_winnings, bet.amount)?;
+        let fee_refunded = refund_bet_fee(&mut ctx.accounts.vault, market, bet)?;
+        let winnings = net_winnings(gross_winnings, fee_taken, fee_refunded)?;
+        require!(amount <= winnings, ErrorCode::InsufficientWinnings);
+
+        transfer_from_vault(
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.claimant_token_account,
+            &ctx.accounts.token_program,
+            winnings,
+        )?;
+
+        bet.is_claimed = true;
+        bet.claimed_amount = winnings;
+        bet.claimed_timestamp = now;
+
+        emit!(WinningsClaimed {
+            market: market.key(),
+            claimant: ctx.accounts.claimant.key(),
+            amount: winnings,
+            gross_winnings,
+            fee_taken,
+            fee_refunded,
+            original_stake: bet.amount,
+            is_refund: is_no_contest(market),
+            mint_decimals: market.mint_decimals,
+            timestamp: now,
+        });
+        emit_vault_balance_snapshot(market, &ctx.accounts.vault_token_account, now)?;
+
+        let new_market = &ctx.accounts.new_market;
+        validate_bet_window(new_market, amount, now, false)?;
+        require_valid_outcome(market_pools(new_market), outcome_index)?;
+        let nullifier = admit_bettor(
+            &ctx.accounts.vault,
+            new_market,
+            &ctx.accounts.claimant.key(),
+            &allowlist_proof,
+            nullifier,
+            &nullifier_secret,
+            ctx.accounts.nullifier_account.as_mut(),
+            ctx.bumps.nullifier_account,
+        )?;
+
+        book_bet(
+            BetAccounts {
+                vault: &mut ctx.accounts.vault,
+                market: &mut ctx.accounts.new_market,
+                bet_account: &mut ctx.accounts.new_bet_account,
+                bettor: &ctx.accounts.claimant,
+                bettor_token_account: &ctx.accounts.claimant_token_account,
+                vault_token_account: &ctx.accounts.vault_token_account,
+                token_program: &ctx.accounts.token_program,
+            },
+            amount,
+            outcome_index,
+            nullifier,
+            memo,
+            false,
+            now,
+        )
+    }
+
+    /// Claim winning bets across several resolved markets with a single payout transfer.
+    /// Remaining accounts are `(market, bet)` pairs, each market at most once, all settling
+    /// in the claimant token account's mint; `proofs[i]` proves ownership of the i-th bet.
+    pub fn claim_winnings_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimWinningsBatch<'info>>,
+        proofs: Vec<Vec<u8>>,
+    ) -> Result<()> {
+        let pairs = ctx.remaining_accounts.chunks_exact(2);
+        require!(
+            pairs.remainder().is_empty() && pairs.len() == proofs.len(),
+            ErrorCode::InvalidClaimBatch
+        );
+        require!(
+            !proofs.is_empty() && proofs.len() <= MAX_CLAIM_BATCH_SIZE,
+            ErrorCode::InvalidClaimBatch
+        );
+
+        let vault_key = ctx.accounts.vault.key();
+        let claimant = ctx.accounts.claimant.key();
+        let now = current_timestamp()?;
+        let mut seen_markets: Vec<Pubkey> = Vec::with_capacity(proofs.len());
+        let mut claimed_totals: Vec<(u64, u64)> = Vec::with_capacity(proofs.len());
+        let mut total_winnings: u64 = 0;
+
+        for (pair, proof) in pairs.zip(proofs.iter()) {
+            let mut market = Account::<Market>::try_from(&pair[0])?;
+            let mut bet = Account::<BetAccount>::try_from(&pair[1])?;
+            // Each market is loaded independently, so a repeat would overwrite the
+            // claimed total booked by the earlier copy
+            require!(
+                !seen_markets.contains(&market.key()),
+                ErrorCode::InvalidClaimBatch
+            );
+            seen_markets.push(market.key());
+            require!(market.vault == vault_key, ErrorCode::InvalidMarketVault);
+            require!(bet.market == market.key(), ErrorCode::BetMarketMismatch);
+            require_settlement_mint(
+                &market,
+                &[
+                    &ctx.accounts.vault_token_account,
+                    &ctx.accounts.claimant_token_account,
+                ],
+            )?;
+
+            let gross_winnings = settle_claim(&mut market, &bet, &claimant, proof)?;
+            let fee_taken =
+                take_winnings_fee(&mut ctx.accounts.vault, &market, gross_winnings, bet.amount)?;
+            let fee_refunded = refund_bet_fee(&mut ctx.accounts.vault, &mut market, &bet)?;
+            let winnings = net_winnings(gross_winnings, fee_taken, fee_refunded)?;
+            bet.is_claimed = true;
+            bet.claimed_amount = winnings;
+            bet.claimed_timestamp = now;
+            total_winnings = total_winnings
+                .checked_add(winnings)
+                .ok_or(ErrorCode::MathOverflow)?;
+
+            emit!(WinningsClaimed {
+                market: market.key(),
+                claimant,
+                amount: winnings,
+                gross_winnings,
+                fee_taken,
+                fee_refunded,
+                original_stake: bet.amount,
+                is_refund: is_no_contest(&market),
+                mint_decimals: market.mint_decimals,
+                timestamp: now,
+            });
+
+            claimed_totals.push((market.total_claimed, market.total_claimable));
+            market.exit(&crate::ID)?;
+            bet.exit(&crate::ID)?;
+        }
+
+        transfer_from_vault(
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.claimant_token_account,
+            &ctx.accounts.token_program,
+            total_winnings,
+        )?;
+
+        // One payout covers the whole batch, so every market reports the same balance
+        let vault_balance = token_balance(&ctx.accounts.vault_token_account)?;
+        for (market, (total_claimed, total_claimable)) in seen_markets.iter().zip(claimed_totals) {
+            emit!(VaultBalanceSnapshot {
+                market: *market,
+                vault_balance,
+                total_claimed,
+                total_claimable,
+                timestamp: now,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Claim into a vesting escrow bound to the claimant instead of paying out immediately.
+    /// Funds stay in the vault token account and are released linearly by `release_vested`.
+    pub fn claim_winnings_vested(
+        ctx: Context<ClaimWinningsVested>,
+        proof: Vec<u8>,
+        vesting_duration: u64,
+    ) -> Result<()> {
+        require_feature(&ctx.accounts.vault, FEATURE_VESTED_CLAIMS)?;
+        require!(vesting_duration > 0, ErrorCode::InvalidVestingSchedule);
+        let bet = &mut ctx.accounts.bet_account;
+        let market = &mut ctx.accounts.market;
+        let now = current_timestamp()?;
+
+        require!(market.vault == ctx.accounts.vault.key(), ErrorCode::InvalidMarketVault);
+        require!(bet.market == market.key(), ErrorCode::BetMarketMismatch);
+        let gross_winnings = settle_claim(market, bet, &ctx.accounts.claimant.key(), &proof)?;
+        let fee_taken =
+            take_winnings_fee(&mut ctx.accounts.vault, market, gross_winnings, bet.amount)?;
+        let fee_refunded = refund_bet_fee(&mut ctx.accounts.vault, market, bet)?;
+        let winnings = net_winnings(gross_winnings, fee_taken, fee_refunded)?;
+
+        let escrow = &mut ctx.accounts.vesting_escrow;
+        escrow.vault = ctx.accounts.vault.key();
+        escrow.market = market.key();
+        escrow.bet = bet.key();
+        escrow.beneficiary = ctx.accounts.claimant.key();
+        escrow.total_amount = winnings;
+        escrow.released_amount = 0;
+        escrow.start_time = now;
+        escrow.end_time = checked_add_seconds(now, vesting_duration)?;
+        escrow.settlement_mint = market.settlement_mint;
+
+        bet.is_claimed = true;
+        bet.claimed_amount = winnings;
+        bet.claimed_timestamp = now;
+
+        emit!(WinningsVested {
+            market: market.key(),
+            claimant: escrow.beneficiary,
+            escrow: escrow.key(),
+            amount: winnings,
+            end_time: escrow.end_time,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Release the portion of a vesting escrow that has vested so far
+    pub fn release_vested(ctx: Context<ReleaseVested>) -> Result<()> {
+        let escrow = &ctx.accounts.vesting_escrow;
+        let now = current_timestamp()?;
+
+        require!(escrow.vault == ctx.accounts.vault.key(), ErrorCode::InvalidMarketVault);
+        require!(escrow.market == ctx.accounts.market.key(), ErrorCode::BetMarketMismatch);
+        require!(
+            ctx.accounts.beneficiary.key() == escrow.beneficiary
+                && ctx.accounts.beneficiary_token_account.owner == escrow.beneficiary,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.vault_token_account.mint == escrow.settlement_mint
+                && ctx.accounts.beneficiary_token_account.mint == escrow.settlement_mint,
+            ErrorCode::SettlementMintMismatch
+        );
+
+        let releasable = calculate_vested_amount(escrow, now)?
+            .checked_sub(escrow.released_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(releasable > 0, ErrorCode::NothingVested);
+
+        transfer_from_vault(
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.beneficiary_token_account,
+            &ctx.accounts.token_program,
+            releasable,
+        )?;
+
+        let escrow = &mut ctx.accounts.vesting_escrow;
+        escrow.released_amount = escrow
+            .released_amount
+            .checked_add(releasable)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(VestedReleased {
+            escrow: escrow.key(),
+            beneficiary: escrow.beneficiary,
+            amount: releasable,
+            total_released: escrow.released_amount,
+            timestamp: now,
+        });
+        emit_vault_balance_snapshot(
+            &ctx.accounts.market,
+            &ctx.accounts.vault_token_account,
+            now,
+        )?;
+
+        Ok(())
+    }
+
+    /// Add liquidity, minting LP tokens to the provider. The first deposit binds the market
+    /// to its LP mint, which must have the vault PDA as mint authority and no supply.
+    pub fn add_liquidity(
+        ctx: Context<AddLiquidity>,
+        amount: u64,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let vault = &mut ctx.accounts.vault;
+
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        require!(market.vault == vault.key(), ErrorCode::InvalidMarketVault);
+        require_inflows_open(vault)?;
+        require_vault_token_account(&ctx.accounts.vault_token_account, &vault.key())?;
+        require_settlement_mint(
+            market,
+            &[
+                &ctx.accounts.provider_token_account,
+                &ctx.accounts.vault_token_account,
+            ],
+        )?;
+        require_liquidity_within_cap(market, amount)?;
+        require_lp_mint(market, &ctx.accounts.lp_mint, &vault.key())?;
+        require_lp_token_account(
+            market,
+            &ctx.accounts.provider_lp_token_account,
+            &ctx.accounts.provider.key(),
+        )?;
+
+        // Transfer tokens to vault; only what actually arrived is credited
+        let received = receive_into_vault(
+            &ctx.accounts.provider_token_account,
+            &ctx.accounts.vault_token_account,
+            ctx.accounts.provider.to_account_info(),
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+
+        // Price the deposit against the liquidity already locked per LP token
+        let lp_tokens =
+            calculate_lp_tokens(received, market.liquidity_locked, market.total_lp_tokens)?;
+        require!(lp_tokens > 0, ErrorCode::ZeroAmount);
+        mint_lp_tokens(
+            vault,
+            &ctx.accounts.lp_mint,
+            &ctx.accounts.provider_lp_token_account,
+            &ctx.accounts.token_program,
+            lp_tokens,
+        )?;
+
+        market.liquidity_locked = market
+            .liquidity_locked
+            .checked_add(received)
+            .ok_or(ErrorCode::MathOverflow)?;
+        market.total_lp_tokens = market
+            .total_lp_tokens
+            .checked_add(lp_tokens)
+            .ok_or(ErrorCode::MathOverflow)?;
+        vault.total_liquidity_locked = vault
+            .total_liquidity_locked
+            .checked_add(received)
+            .ok_or(ErrorCode::MathOverflow)?;
+        // A deeper `b` flattens the curve, so the price moves toward 50% as liquidity grows
+        refresh_implied_probability(market);
+
+        emit!(LiquidityAdded {
+            market: market.key(),
+            provider: ctx.accounts.provider.key(),
+            amount: received,
+            lp_tokens,
+            total_liquidity_locked: vault.total_liquidity_locked,
+            implied_probability: market.implied_probability,
+            mint_decimals: market.mint_decimals,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Burn LP tokens for their share of the market's liquidity. Open markets price the exit
+    /// against live liquidity less the early-exit fee, which stays with the remaining LPs;
+    /// settled markets pay the exact share of the settlement snapshot.
+    pub fn remove_liquidity(ctx: Context<RemoveLiquidity>, lp_amount: u64) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let vault = &mut ctx.accounts.vault;
+        let now = current_timestamp()?;
+
+        require!(lp_amount > 0, ErrorCode::ZeroAmount);
+        require!(market.vault == vault.key(), ErrorCode::InvalidMarketVault);
+        require_vault_token_account(&ctx.accounts.vault_token_account, &vault.key())?;
+        require_settlement_mint(
+            market,
+            &[
+                &ctx.accounts.provider_token_account,
+                &ctx.accounts.vault_token_account,
+            ],
+        )?;
+        require!(
+            market.lp_mint != Pubkey::default() && ctx.accounts.lp_mint.key() == market.lp_mint,
+            ErrorCode::InvalidLpMint
+        );
+        require_lp_token_account(
+            market,
+            &ctx.accounts.provider_lp_token_account,
+            &ctx.accounts.provider.key(),
+        )?;
+        require!(
+            lp_amount <= market.total_lp_tokens,
+            ErrorCode::LpEntitlementExceeded
+        );
+
+        let (amount, exit_fee) = withdraw_lp_liquidity(vault, market, lp_amount, now)?;
+        if !market.is_resolved {
+            // A shallower `b` steepens the curve again as liquidity leaves
+            refresh_implied_probability(market);
+        }
+
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    from: ctx.accounts.provider_lp_token_account.to_account_info(),
+                    authority: ctx.accounts.provider.to_account_info(),
+                },
+            ),
+            lp_amount,
+        )?;
+        transfer_from_vault(
+            vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.provider_token_account,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+
+        emit!(LiquidityRemoved {
+            market: market.key(),
+            provider: ctx.accounts.provider.key(),
+            amount,
+            lp_tokens: lp_amount,
+            exit_fee,
+            total_liquidity_locked: vault.total_liquidity_locked,
+            implied_probability: market.implied_probability,
+            mint_decimals: market.mint_decimals,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Fund the insurance pool backing the insured share of losing stakes
+    pub fn deposit_insurance(ctx: Context<DepositInsurance>, amount: u64) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        require!(
+            ctx.accounts.creator.key() == market.creator,
+            ErrorCode::Unauthorized
+        );
+        require!(market.insured_basis_points > 0, ErrorCode::InvalidInsurance);
+        require!(!market.is_resolved, ErrorCode::MarketResolved);
+        require!(market.vault == ctx.accounts.vault.key(), ErrorCode::InvalidMarketVault);
+        require_inflows_open(&ctx.accounts.vault)?;
+        require_vault_token_account(&ctx.accounts.vault_token_account, &market.vault)?;
+        require_settlement_mint(
+            market,
+            &[
+                &ctx.accounts.creator_token_account,
+                &ctx.accounts.vault_token_account,
+            ],
+        )?;
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.creator_token_account.to_account_info(),
+            to: ctx.accounts.vault_token_account.to_account_info(),
+            authority: ctx.accounts.creator.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+            amount,
+        )?;
+
+        market.insurance_pool = market
+            .insurance_pool
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(InsuranceDeposited {
+            market: market.key(),
+            amount,
+            insurance_pool: market.insurance_pool,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Configure the share of fees routed to a public-goods recipient
+    pub fn set_donation_config(
+        ctx: Context<SetDonationConfig>,
+        donation_basis_points: u16,
+        donation_recipient: Pubkey,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        // Donations are carved out of the fee, so the total charged stays within the fee cap
+        require!(donation_basis_points <= 10_000, ErrorCode::InvalidDonationConfig);
+        require!(
+            donation_basis_points == 0 || donation_recipient != Pubkey::default(),
+            ErrorCode::InvalidDonationConfig
+        );
+
+        vault.donation_basis_points = donation_basis_points;
+        vault.donation_recipient = donation_recipient;
+
+        emit!(DonationConfigUpdated {
+            vault: vault.key(),
+            donation_basis_points,
+            donation_recipient,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Send accrued donations to the configured recipient
+    pub fn distribute_donations(ctx: Context<DistributeDonations>) -> Result<()> {
+        let amount = ctx.accounts.vault.pending_donations;
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        require!(
+            ctx.accounts.recipient_token_account.owner == ctx.accounts.vault.donation_recipient,
+            ErrorCode::InvalidDonationRecipient
+        );
+
+        transfer_from_vault(
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.recipient_token_account,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.pending_donations = 0;
+        vault.total_donations_distributed = vault
+            .total_donations_distributed
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(DonationsDistributed {
+            vault: vault.key(),
+            recipient: vault.donation_recipient,
+            amount,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Transfer up to the un-withdrawn balance of collected fees in the vault's own mint to a
+    /// token account owned by the authority
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        require_vault_token_account(&ctx.accounts.vault_token_account, &vault.key())?;
+        require!(
+            ctx.accounts.vault_token_account.mint == vault.settlement_mint,
+            ErrorCode::SettlementMintMismatch
+        );
+        require!(
+            ctx.accounts.destination_token_account.owner == vault.authority,
+            ErrorCode::InvalidFeeDestination
+        );
+        let available = vault
+            .total_fees_collected
+            .checked_sub(vault.total_fees_withdrawn)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(amount <= available, ErrorCode::InsufficientFees);
+
+        transfer_from_vault(
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.destination_token_account,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.total_fees_withdrawn = vault
+            .total_fees_withdrawn
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(FeesWithdrawn {
+            vault: vault.key(),
+            destination: ctx.accounts.destination_token_account.key(),
+            amount,
+            total_fees_withdrawn: vault.total_fees_withdrawn,
+            remaining: available - amount,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Configure the maximum haircut charged to LPs withdrawing before resolution
+    pub fn set_early_exit_fee(
+        ctx: Context<SetEarlyExitFee>,
+        early_exit_fee_basis_points: u16,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        validate_early_exit_fee(early_exit_fee_basis_points, &ctx.accounts.config)?;
+
+        vault.early_exit_fee_basis_points = early_exit_fee_basis_points;
+
+        emit!(EarlyExitFeeUpdated {
+            vault: vault.key(),
+            early_exit_fee_basis_points,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Bound the depth of allowlist merkle proofs accepted by bet instructions
+    pub fn set_max_merkle_depth(ctx: Context<SetMaxMerkleDepth>, max_merkle_depth: u8) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(max_merkle_depth > 0, ErrorCode::InvalidMerkleDepth);
+
+        vault.max_merkle_depth = max_merkle_depth;
+
+        emit!(MaxMerkleDepthUpdated {
+            vault: vault.key(),
+            max_merkle_depth,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Configure the claim-time winnings fee and whether it applies to gross payouts or profit
+    pub fn set_winnings_fee(
+        ctx: Context<SetWinningsFee>,
+        winnings_fee_basis_points: u16,
+        winnings_fee_basis: WinningsFeeBasis,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            winnings_fee_basis_points <= ctx.accounts.config.max_fee_basis_points,
+            ErrorCode::InvalidFee
+        );
+
+        vault.winnings_fee_basis_points = winnings_fee_basis_points;
+        vault.winnings_fee_basis = winnings_fee_basis;
+
+        emit!(WinningsFeeUpdated {
+            vault: vault.key(),
+            winnings_fee_basis_points,
+            winnings_fee_basis,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Choose how oracles sign resolutions for markets created from now on
+    pub fn set_oracle_signature_scheme(
+        ctx: Context<SetOracleSignatureScheme>,
+        oracle_signature_scheme: OracleSignatureScheme,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        vault.oracle_signature_scheme = oracle_signature_scheme;
+
+        emit!(OracleSignatureSchemeUpdated {
+            vault: vault.key(),
+            oracle_signature_scheme,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Set the share of the losing pool burned at resolution for markets created from now on
+    pub fn set_burn_basis_points(
+        ctx: Context<SetBurnBasisPoints>,
+        burn_basis_points: u16,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(burn_basis_points <= 10_000, ErrorCode::InvalidBurnConfig);
+
+        vault.burn_basis_points = burn_basis_points;
+
+        emit!(BurnConfigUpdated {
+            vault: vault.key(),
+            burn_basis_points,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Set the share of the bet fee rebated to bets that shrink a market's pool imbalance
+    pub fn set_balancing_rebate(
+        ctx: Context<SetBalancingRebate>,
+        balancing_rebate_basis_points: u16,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            balancing_rebate_basis_points <= 10_000,
+            ErrorCode::InvalidFee
+        );
+
+        vault.balancing_rebate_basis_points = balancing_rebate_basis_points;
+
+        emit!(BalancingRebateUpdated {
+            vault: vault.key(),
+            balancing_rebate_basis_points,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Configure odds-scaled minimum bets: above `threshold_bps` implied probability for the
+    /// side being bet, its minimum rises toward `max_multiplier` times the market minimum.
+    /// A zero threshold disables scaling.
+    pub fn set_odds_scaled_min_bet(
+        ctx: Context<SetOddsScaledMinBet>,
+        threshold_bps: u16,
+        max_multiplier: u16,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            threshold_bps < 10_000 && max_multiplier >= 1,
+            ErrorCode::InvalidOddsScaling
+        );
+
+        vault.odds_scaled_min_threshold_bps = threshold_bps;
+        vault.odds_scaled_min_max_multiplier = max_multiplier;
+
+        emit!(OddsScaledMinBetUpdated {
+            vault: vault.key(),
+            threshold_bps,
+            max_multiplier,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Set the per-bet keeper tip for markets created from now on
+    pub fn set_keeper_tip(
+        ctx: Context<SetKeeperTip>,
+        keeper_tip_basis_points: u16,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            keeper_tip_basis_points <= MAX_KEEPER_TIP_BPS,
+            ErrorCode::InvalidFee
+        );
+
+        vault.keeper_tip_basis_points = keeper_tip_basis_points;
+
+        emit!(KeeperTipUpdated {
+            vault: vault.key(),
+            keeper_tip_basis_points,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Return the (pubkey, id) of markets with sequence `start..start + count` via return data.
+    /// The markets are passed as remaining accounts in sequence order and each is checked to
+    /// belong to the vault and to sit at its expected sequence, so a page is always complete.
+    pub fn get_market_list_page<'info>(
+        ctx: Context<'_, '_, '_, 'info, GetMarketListPage<'info>>,
+        start: u64,
+        count: u8,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(
+            count as usize <= MAX_MARKET_PAGE_SIZE,
+            ErrorCode::PageTooLarge
+        );
+
+        let end = start.saturating_add(count as u64).min(vault.market_count);
+        let page_len = end.saturating_sub(start) as usize;
+        require!(
+            ctx.remaining_accounts.len() == page_len,
+            ErrorCode::IncompletePage
+        );
+
+        let mut entries = Vec::with_capacity(page_len);
+        for (offset, account_info) in ctx.remaining_accounts.iter().enumerate() {
+            let market = Account::<Market>::try_from(account_info)?;
+            require!(market.vault == vault.key(), ErrorCode::InvalidMarketVault);
+            require!(
+                market.sequence == start + offset as u64,
+                ErrorCode::IncompletePage
+            );
+            entries.push(MarketListEntry {
+                market: market.key(),
+                id: market.id,
+            });
+        }
+
+        set_return_data(&entries.try_to_vec()?);
+
+        Ok(())
+    }
+
+    /// Return a bettor's bets in a market with summary stats via return data. The bets are
+    /// passed as remaining accounts, each at most once, and must all be the bettor's bets
+    /// in this market.
+    pub fn get_bettor_history<'info>(
+        ctx: Context<'_, '_, '_, 'info, GetBettorHistory<'info>>,
+        bettor: Pubkey,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(
+            ctx.remaining_accounts.len() <= MAX_BETTOR_HISTORY_SIZE,
+            ErrorCode::PageTooLarge
+        );
+
+        let mut history = BettorHistory {
+            bets: Vec::with_capacity(ctx.remaining_accounts.len()),
+            staked_by_outcome: vec![0; market.outcome_count as usize],
+            total_claimed: 0,
+            claimed_count: 0,
+        };
+        for account_info in ctx.remaining_accounts.iter() {
+            let bet = Account::<BetAccount>::try_from(account_info)?;
+            require!(bet.market == market.key(), ErrorCode::BetMarketMismatch);
+            require!(
+                bet.bettor == bettor && !history.bets.contains(&bet.key()),
+                ErrorCode::InvalidBettorHistory
+            );
+            history.bets.push(bet.key());
+            add_outcome_stake(&mut history.staked_by_outcome, &bet)?;
+            if bet.is_claimed {
+                history.total_claimed = history
+                    .total_claimed
+                    .checked_add(bet.claimed_amount)
+                    .ok_or(ErrorCode::MathOverflow)?;
+                history.claimed_count += 1;
+            }
+        }
+
+        set_return_data(&history.try_to_vec()?);
+
+        Ok(())
+    }
+
+    /// Recompute `total_volume` from bet accounts, passed as remaining accounts in sequence
+    /// order over as many batches as needed. A pass starts at `start == 0` and every later
+    /// batch must continue exactly where the previous one stopped. Only the batch that reaches
+    /// `bet_count` completes the pass, and only with `overwrite` does it replace the counter.
+    pub fn reconcile_volume<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReconcileVolume<'info>>,
+        start: u64,
+        overwrite: bool,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        if start == 0 {
+            vault.volume_reconcile_sum = 0;
+        } else {
+            require!(
+                start == vault.volume_reconcile_cursor,
+                ErrorCode::ReconciliationOutOfOrder
+            );
+        }
+
+        let mut sum = vault.volume_reconcile_sum;
+        for (offset, account_info) in ctx.remaining_accounts.iter().enumerate() {
+            let bet = Account::<BetAccount>::try_from(account_info)?;
+            require!(bet.vault == vault.key(), ErrorCode::InvalidMarketVault);
+            require!(
+                bet.sequence == start + offset as u64,
+                ErrorCode::ReconciliationOutOfOrder
+            );
+            sum = sum
+                .checked_add(bet.volume_amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        vault.volume_reconcile_sum = sum;
+        vault.volume_reconcile_cursor = start + ctx.remaining_accounts.len() as u64;
+
+        let complete = vault.volume_reconcile_cursor == vault.bet_count;
+        require!(!overwrite || complete, ErrorCode::ReconciliationIncomplete);
+        if complete {
+            let recorded_volume = vault.total_volume;
+            if overwrite {
+                vault.total_volume = sum;
+            }
+            emit!(VolumeReconciled {
+                vault: vault.key(),
+                recorded_volume,
+                reconciled_volume: sum,
+                bet_count: vault.bet_count,
+                overwritten: overwrite,
+                timestamp: current_timestamp()?,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Run one batch of a resolved market's settlement pass, folding each bet into the
+    /// entitlement merkle tree. Bets are passed as remaining accounts in `market_index` order
+    /// and the batch must start at `settlement_cursor`, so a keeper that hit compute limits
+    /// resumes where it stopped and a replayed batch is rejected rather than counted twice.
+    /// The batch that reaches the market's `bet_count` stores the root and completes the
+    /// pass. Losing bets are included with a zero entitlement. Callable by anyone.
+    pub fn commit_entitlements<'info>(
+        ctx: Context<'_, '_, '_, 'info, CommitEntitlements<'info>>,
+        start: u64,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(market.is_resolved, ErrorCode::MarketNotResolved);
+        require!(!market.settlement_complete, ErrorCode::SettlementAlreadyComplete);
+        require!(
+            start == market.settlement_cursor,
+            ErrorCode::SettlementBatchOutOfOrder
+        );
+        require!(
+            market.bet_count <= 1 << ENTITLEMENT_TREE_DEPTH,
+            ErrorCode::EntitlementTreeFull
+        );
+
+        let market_key = market.key();
+        let mut cursor = start;
+        for account_info in ctx.remaining_accounts.iter() {
+            let bet = Account::<BetAccount>::try_from(account_info)?;
+            require!(bet.market == market_key, ErrorCode::BetMarketMismatch);
+            require!(
+                bet.market_index == cursor,
+                ErrorCode::SettlementBatchOutOfOrder
+            );
+            let entitlement = bet_entitlement(market, &bet)?.unwrap_or(0);
+            append_entitlement_leaf(
+                &mut market.entitlement_frontier,
+                cursor,
+                entitlement_leaf(&bet.bettor, &bet.key(), entitlement),
+            );
+            cursor += 1;
+        }
+        market.settlement_cursor = cursor;
+        market.settlement_complete = cursor == market.bet_count;
+
+        let now = current_timestamp()?;
+        emit!(SettlementProgress {
+            market: market_key,
+            start,
+            settlement_cursor: cursor,
+            bet_count: market.bet_count,
+            settlement_complete: market.settlement_complete,
+            timestamp: now,
+        });
+
+        if market.settlement_complete {
+            market.entitlement_root = entitlement_tree_root(&market.entitlement_frontier, cursor);
+            emit!(EntitlementRootCommitted {
+                market: market_key,
+                entitlement_root: market.entitlement_root,
+                bet_count: cursor,
+                settlement_generation: market.settlement_generation,
+                timestamp: now,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Check a bet's entitlement against the market's committed entitlement root; fails
+    /// with `InvalidEntitlementProof` unless the proof holds
+    pub fn verify_entitlement_proof(
+        ctx: Context<VerifyEntitlementProof>,
+        bettor: Pubkey,
+        bet: Pubkey,
+        entitlement: u64,
+        leaf_index: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(
+            market.entitlement_root != [0u8; 32],
+            ErrorCode::EntitlementsNotCommitted
+        );
+        require!(
+            verify_entitlement(
+                &market.entitlement_root,
+                &bettor,
+                &bet,
+                entitlement,
+                leaf_index,
+                &proof,
+            ),
+            ErrorCode::InvalidEntitlementProof
+        );
+        Ok(())
+    }
+
+    /// Emit a snapshot of the vault's running counters for indexers to anchor on
+    pub fn emit_vault_stats(ctx: Context<EmitVaultStats>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        emit!(VaultStats {
+            vault: vault.key(),
+            total_volume: vault.total_volume,
+            total_fees_collected: vault.total_fees_collected,
+            total_fees_withdrawn: vault.total_fees_withdrawn,
+            pending_donations: vault.pending_donations,
+            total_donations_distributed: vault.total_donations_distributed,
+            total_liquidity_locked: vault.total_liquidity_locked,
+            market_count: vault.market_count,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Recompute the vault's accounting invariants against its token balance and return
+    /// a `HEALTH_*` status bitfield via return data. Read-only; zero means all clear.
+    pub fn health_check(ctx: Context<HealthCheck>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let token_account = &ctx.accounts.vault_token_account;
+        let balance = token_account.amount;
+
+        let mut status = 0u32;
+        let fees = vault
+            .total_fees_collected
+            .checked_add(vault.pending_donations);
+        // Withdrawn fees have left the vault and no longer need covering by its balance
+        let retained_fees = fees.and_then(|fees| fees.checked_sub(vault.total_fees_withdrawn));
+        if retained_fees.map_or(true, |fees| fees > balance) {
+            status |= HEALTH_FEES_EXCEED_BALANCE;
+        }
+        if vault.total_liquidity_locked > balance {
+            status |= HEALTH_LIQUIDITY_EXCEEDS_BALANCE;
+        }
+        let obligations =
+            retained_fees.and_then(|fees| fees.checked_add(vault.total_liquidity_locked));
+        if obligations.map_or(true, |obligations| obligations > balance) {
+            status |= HEALTH_OBLIGATIONS_EXCEED_BALANCE;
+        }
+        if fees.map_or(true, |fees| fees > vault.total_volume) {
+            status |= HEALTH_FEES_EXCEED_VOLUME;
+        }
+        if token_account.mint != vault.settlement_mint {
+            status |= HEALTH_SETTLEMENT_MINT_MISMATCH;
+        }
+        if vault.program_version > CURRENT_PROGRAM_VERSION {
+            status |= HEALTH_UNKNOWN_PROGRAM_VERSION;
+        }
+
+        set_return_data(&status.to_le_bytes());
+
+        Ok(())
+    }
+
+    /// Configure how far and how many times a market's resolution may be extended
+    pub fn set_resolution_extension_policy(
+        ctx: Context<SetResolutionExtensionPolicy>,
+        resolution_extension_seconds: u64,
+        max_resolution_extensions: u8,
+        max_total_extension_seconds: u64,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        vault.resolution_extension_seconds = resolution_extension_seconds;
+        vault.max_resolution_extensions = max_resolution_extensions;
+        vault.max_total_extension_seconds = max_total_extension_seconds;
+        require_lockup_horizon(vault)?;
+
+        emit!(ResolutionExtensionPolicyUpdated {
+            vault: vault.key(),
+            resolution_extension_seconds,
+            max_resolution_extensions,
+            max_total_extension_seconds,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Choose whether new markets use nullifier-bound bets and ZK-proven claims
+    pub fn set_privacy_enabled(ctx: Context<SetPrivacyEnabled>, privacy_enabled: bool) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        vault.privacy_enabled = privacy_enabled;
+
+        emit!(PrivacyPolicyUpdated {
+            vault: vault.key(),
+            privacy_enabled,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Allow or forbid markets whose creator is also their oracle
+    pub fn set_allow_self_oracle(
+        ctx: Context<SetAllowSelfOracle>,
+        allow_self_oracle: bool,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        vault.allow_self_oracle = allow_self_oracle;
+
+        emit!(SelfOraclePolicyUpdated {
+            vault: vault.key(),
+            allow_self_oracle,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Accept an additional settlement mint for markets in this vault. Mints cannot be
+    /// removed again, since open markets and `rescue_tokens` rely on the registry.
+    pub fn add_settlement_mint(ctx: Context<AddSettlementMint>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        let mint = ctx.accounts.mint.key();
+        require!(
+            !is_settlement_mint(vault, &mint),
+            ErrorCode::UnsupportedSettlementMint
+        );
+        let slot = extra_settlement_mint_slot(vault, &Pubkey::default())
+            .ok_or(ErrorCode::SettlementMintLimitReached)?;
+        vault.extra_settlement_mints[slot] = mint;
+        vault.extra_settlement_mint_decimals[slot] = ctx.accounts.mint.decimals;
+
+        emit!(SettlementMintAdded {
+            vault: vault.key(),
+            mint,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Recover tokens of a foreign mint mistakenly sent to the vault. Accepted settlement
+    /// mints can never be rescued, so user funds cannot be drained through this path.
+    pub fn rescue_tokens(ctx: Context<RescueTokens>, amount: u64) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        require!(
+            !is_settlement_mint(vault, &ctx.accounts.source_token_account.mint),
+            ErrorCode::CannotRescueSettlementMint
+        );
+
+        transfer_from_vault(
+            vault,
+            &ctx.accounts.source_token_account,
+            &ctx.accounts.destination_token_account,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+
+        emit!(TokensRescued {
+            vault: vault.key(),
+            mint: ctx.accounts.source_token_account.mint,
+            destination: ctx.accounts.destination_token_account.key(),
+            amount,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Preview the price a hypothetical bet would move the market to, via return data.
+    /// The stake is netted of the market fee exactly as `place_bet` would and spent on the
+    /// LMSR curve from a copy of the share quantities, and no state is written.
+    pub fn odds_preview(ctx: Context<PreviewOdds>, amount: u64, outcome: Outcome) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        require_binary_market(market)?;
+
+        let fee_basis_points = effective_fee_basis_points(market, current_timestamp()?);
+        let (_, _, bet_amount) = split_bet_amount(amount, market, fee_basis_points, false)?;
+
+        let mut shares = market.outcome_shares;
+        let trade = calculate_lmsr_trade(shares, outcome, bet_amount, market.liquidity_locked)?;
+        shares[outcome.index()] = shares[outcome.index()]
+            .checked_add(trade.shares)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let after = calculate_lmsr_quotes(shares[0], shares[1], market.liquidity_locked);
+        let price_after = match outcome {
+            Outcome::Yes => after.0,
+            Outcome::No => after.1,
+        };
+        // Average price paid per share, in basis points
+        let effective_price = if trade.shares == 0 {
+            price_after
+        } else {
+            checked_mul_div(bet_amount as u128, 10_000, trade.shares as u128)?.min(10_000)
+        };
+
+        let preview = OddsPreview {
+            yes_probability: clamp_probability(trade.yes_probability),
+            outcome_probability: price_after,
+            effective_price,
+        };
+        set_return_data(&preview.try_to_vec()?);
+
+        Ok(())
+    }
+
+    /// Configure the minimum gap between market creation and resolution
+    pub fn set_min_resolution_offset(
+        ctx: Context<SetMinResolutionOffset>,
+        min_resolution_offset_seconds: i64,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            min_resolution_offset_seconds >= 0,
+            ErrorCode::InvalidResolutionTime
+        );
+
+        vault.min_resolution_offset_seconds = min_resolution_offset_seconds;
+
+        emit!(MinResolutionOffsetUpdated {
+            vault: vault.key(),
+            min_resolution_offset_seconds,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Cap LP liquidity per market for markets created from now on; zero means unlimited
+    pub fn set_max_liquidity(ctx: Context<SetMaxLiquidity>, max_liquidity: u64) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            max_liquidity == 0 || max_liquidity >= vault.min_initial_liquidity,
+            ErrorCode::InvalidLiquidityCap
+        );
+
+        vault.max_liquidity = max_liquidity;
+
+        emit!(MaxLiquidityUpdated {
+            vault: vault.key(),
+            max_liquidity,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Cap how many unresolved markets one creator may have open at once; zero disables the cap.
+    /// Lowering it below a creator's current count only blocks their further creations.
+    pub fn set_max_open_markets_per_creator(
+        ctx: Context<SetMaxOpenMarketsPerCreator>,
+        max_open_markets_per_creator: u32,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        vault.max_open_markets_per_creator = max_open_markets_per_creator;
+
+        emit!(MaxOpenMarketsPerCreatorUpdated {
+            vault: vault.key(),
+            max_open_markets_per_creator,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Set how long after an oracle resolution the authority may override the outcome, with
+    /// claims locked meanwhile; zero disables overrides. Applies to resolutions from now on.
+    pub fn set_authority_override_window(
+        ctx: Context<SetAuthorityOverrideWindow>,
+        authority_override_window_seconds: u64,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        vault.authority_override_window_seconds = authority_override_window_seconds;
+        require_lockup_horizon(vault)?;
+
+        emit!(AuthorityOverrideWindowUpdated {
+            vault: vault.key(),
+            authority_override_window_seconds,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Bound the total time the vault's configured windows may keep funds locked past a
+    /// market's `resolution_time`. Rejected if the current windows already exceed it.
+    pub fn set_max_total_lockup(
+        ctx: Context<SetMaxTotalLockup>,
+        max_total_lockup_seconds: u64,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        vault.max_total_lockup_seconds = max_total_lockup_seconds;
+        require_lockup_horizon(vault)?;
+
+        emit!(MaxTotalLockupUpdated {
+            vault: vault.key(),
+            max_total_lockup_seconds,
+            configured_lockup_seconds: configured_lockup_seconds(vault),
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Set the fixed stake of one survey vote; zero disables survey voting
+    pub fn set_survey_stake(ctx: Context<SetSurveyStake>, survey_stake: u64) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        vault.survey_stake = survey_stake;
+
+        emit!(SurveyStakeUpdated {
+            vault: vault.key(),
+            survey_stake,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Permanently wind the vault down after a critical bug: every inflow (markets, bets,
+    /// liquidity, insurance, survey stakes) is rejected from now on while claims, refunds
+    /// and withdrawals stay open. There is no way back.
+    pub fn enter_wind_down(ctx: Context<EnterWindDown>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(!vault.wind_down, ErrorCode::VaultWindingDown);
+
+        vault.wind_down = true;
+
+        emit!(VaultWindDownEntered {
+            vault: vault.key(),
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Emergency stop: reject every inflow (markets, bets, liquidity, insurance, survey
+    /// stakes) until `unpause_vault`. Resolution, claims, refunds and withdrawals stay open
+    /// so users can still exit.
+    pub fn pause_vault(ctx: Context<SetVaultPaused>) -> Result<()> {
+        set_vault_paused(&mut ctx.accounts.vault, &ctx.accounts.authority, true)
+    }
+
+    /// Lift a `pause_vault` emergency stop
+    pub fn unpause_vault(ctx: Context<SetVaultPaused>) -> Result<()> {
+        set_vault_paused(&mut ctx.accounts.vault, &ctx.accounts.authority, false)
+    }
+
+    /// Stop new market creation while existing markets keep trading and settling
+    pub fn pause_new_markets(ctx: Context<SetNewMarketsPaused>) -> Result<()> {
+        set_new_markets_paused(&mut ctx.accounts.vault, &ctx.accounts.authority, true)
+    }
+
+    /// Allow market creation again after `pause_new_markets`
+    pub fn resume_new_markets(ctx: Context<SetNewMarketsPaused>) -> Result<()> {
+        set_new_markets_paused(&mut ctx.accounts.vault, &ctx.accounts.authority, false)
+    }
+
+    /// Require every new market to seed at least this much LP liquidity at creation.
+    /// Liquidity is the LMSR `b`: a bet of size `x` moves the price by roughly `x / (4b)`
+    /// around 50%, so this floor is also the vault's minimum `b` and bounds how far a small
+    /// bet can push the odds.
+    pub fn set_min_initial_liquidity(
+        ctx: Context<SetMinInitialLiquidity>,
+        min_initial_liquidity: u64,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        vault.min_initial_liquidity = min_initial_liquidity;
+
+        emit!(MinInitialLiquidityUpdated {
+            vault: vault.key(),
+            min_initial_liquidity,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Enable or disable optional behaviors for this vault (see the `FEATURE_*` bits)
+    pub fn set_feature_flags(ctx: Context<SetFeatureFlags>, feature_flags: u64) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        vault.feature_flags = feature_flags;
+
+        emit!(FeatureFlagsUpdated {
+            vault: vault.key(),
+            program_version: vault.program_version,
+            feature_flags,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Choose whether anyone may create markets or only allowlisted creators
+    pub fn set_creator_policy(
+        ctx: Context<SetCreatorPolicy>,
+        creator_policy: CreatorPolicy,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        vault.creator_policy = creator_policy;
+
+        emit!(CreatorPolicyUpdated {
+            vault: vault.key(),
+            creator_policy,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Allow `creator` to create markets while the vault is allowlisted
+    pub fn add_allowed_creator(ctx: Context<AddAllowedCreator>, creator: Pubkey) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let entry = &mut ctx.accounts.allowed_creator;
+        entry.vault = vault.key();
+        entry.creator = creator;
+
+        emit!(CreatorAllowlistUpdated {
+            vault: vault.key(),
+            creator,
+            allowed: true,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a creator's allowlist entry, returning its rent to the authority
+    pub fn remove_allowed_creator(ctx: Context<RemoveAllowedCreator>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+
+        emit!(CreatorAllowlistUpdated {
+            vault: vault.key(),
+            creator: ctx.accounts.allowed_creator.creator,
+            allowed: false,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Register a labeled outcome set, such as Home/Away/Draw, that creators can attach to
+    /// markets with a matching `outcome_count`. Outcome `i` is labeled `labels[i]`.
+    pub fn create_outcome_schema(
+        ctx: Context<CreateOutcomeSchema>,
+        schema_id: u8,
+        labels: Vec<[u8; 16]>,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(
+            ctx.accounts.authority.key() == vault.authority,
+            ErrorCode::Unauthorized
+        );
+        validate_outcome_labels(&labels)?;
+
+        let schema = &mut ctx.accounts.outcome_schema;
+        schema.vault = vault.key();
+        schema.schema_id = schema_id;
+        schema.outcome_count = labels.len() as u8;
+        schema.labels = [[0u8; 16]; MAX_OUTCOMES];
+        schema.labels[..labels.len()].copy_from_slice(&labels);
+
+        emit!(OutcomeSchemaCreated {
+            vault: vault.key(),
+            schema: schema.key(),
+            schema_id,
+            labels,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Close a settled market's spent accounts, returning rent to whoever paid it. Remaining
+    /// accounts are `(bet, bettor)` pairs; bets of this market with nothing left to claim are
+    /// closed to their bettor and anything else is skipped. With `market_creator` given, the
+    /// market itself is closed too once every claim, keeper tip and LP share is paid out.
+    /// Nullifier accounts carry no market link to check eligibility against and are left alone.
+    pub fn cleanup_settled<'info>(
+        ctx: Context<'_, '_, '_, 'info, CleanupSettled<'info>>,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.is_resolved, ErrorCode::MarketNotResolved);
+        // Closed bets can no longer be processed by the settlement pass
+        require!(market.settlement_complete, ErrorCode::SettlementIncomplete);
+
+        let mut bets_closed: u32 = 0;
+        for pair in ctx.remaining_accounts.chunks_exact(2) {
+            let Ok(bet) = Account::<BetAccount>::try_from(&pair[0]) else {
+                continue;
+            };
+            if bet.market != market.key()
+                || bet.bettor != pair[1].key()
+                || bet_has_open_claim(market, &bet)
+            {
+                continue;
+            }
+            bet.close(pair[1].clone())?;
+            bets_closed += 1;
+        }
+
+        let market_closed = match &ctx.accounts.market_creator {
+            Some(creator) if creator.key() == market.creator && market_fully_settled(market) => {
+                market.close(creator.to_account_info())?;
+                true
+            }
+            _ => false,
+        };
+
+        emit!(SettledAccountsClosed {
+            market: market.key(),
+            bets_closed,
+            market_closed,
+            timestamp: current_timestamp()?,
+        });
+
+        Ok(())
+    }
+
+    /// Quote the parimutuel payout of a stake on `outcome` if that outcome wins, via return
+    /// data. In parimutuel a new stake inflates both the total and the winning pool, so its
+    /// own money partly pays itself and slightly dilutes existing winners. By default the quote
+    /// applies the stake to the pools (the marginal payout the bettor would actually get);
+    /// with `exclude_self_in_quote` it prices against the current pools only, which is the
+    /// right figure for an existing position. `claim_winnings` always uses the recorded
+    /// pools, so quotes never affect conservation.
+    pub fn get_payout_quote(
+        ctx: Context<GetPayoutQuote>,
+        amount: u64,
+        outcome: Outcome,
+        exclude_self_in_quote: bool,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        require_binary_market(market)?;
+
+        let fee_basis_points = effective_fee_basis_points(market, current_timestamp()?);
+        let (_, _, stake) = split_bet_amount(amount, market, fee_basis_points, false)?;
+
+        let mut pools = outcome_pools(market);
+        if !exclude_self_in_quote {
+            pools[outcome.index()] = pools[outcome.index()]
+                .checked_add(stake)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+
+        let winning_pool = pools[outcome.index()];
+        let total_pool = pools[0]
+            .checked_add(pools[1])
+            .ok_or(ErrorCode::MathOverflow)?;
+        let payout = if winning_pool == 0 {
+            stake
+        } else {
+            let burned = calculate_loser_burn(&pools, outcome.index(), market.burn_basis_points)?;
+            calculate_parimutuel_payout(stake, &pools, outcome.index(), burned)?
+        };
+
+        let quote = PayoutQuote {
+            stake,
+            payout,
+            winning_pool,
+            total_pool,
+        };
+        set_return_data(&quote.try_to_vec()?);
+
+        Ok(())
+    }
+
+    /// Return both Yes and No prices from live share quantities via return data
+    pub fn get_quotes(ctx: Context<GetQuotes>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require_binary_market(market)?;
+
+        let (yes_probability, no_probability) = calculate_lmsr_quotes(
+            market.outcome_shares[Outcome::Yes.index()],
+            market.outcome_shares[Outcome::No.index()],
+            market.liquidity_locked,
+        );
+
+        let quotes = Quotes {
+            yes_probability,
+            no_probability,
+            sum: yes_probability + no_probability,
+        };
+        set_return_data(&quotes.try_to_vec()?);
+
+        Ok(())
+    }
+
+    /// Quote a bet of `amount` on `outcome_index` via return data, without transfers or state
+    /// writes. The bet is priced and applied to a copy of the market by the same helpers
+    /// `place_bet` books through, and the payout is what a claim would owe if the outcome
+    /// won against the resulting pools. Assumes the full `amount` arrives, as it does for
+    /// mints without transfer fees.
+    pub fn quote_bet(ctx: Context<QuoteBet>, amount: u64, outcome_index: u8) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let market = &ctx.accounts.market;
+        let now = current_timestamp()?;
+
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        require!(market.vault == vault.key(), ErrorCode::InvalidMarketVault);
+        require_inflows_open(vault)?;
+        validate_bet_window(market, amount, now, false)?;
+
+        let quote = project_bet(vault, market, amount, outcome_index, now)?;
+        set_return_data(&quote.try_to_vec()?);
+
+        Ok(())
+    }
+}
+
+// ===== Helper Functions =====
+
+/// Parameters shared by every market creation path
+struct MarketParams {
+    id: [u8; 32],
+    vault: Pubkey,
+    creator: Pubkey,
+    oracle: Pubkey,
+    resolution_time: i64,
+    /// `None` disables commit-reveal for the market
+    commitment_hash: Option<[u8; 32]>,
+    fee_basis_points: u16,
+    fee_curve: FeeCurve,
+    fee_curve_max_basis_points: u16,
+    min_bet_amount: u64,
+    is_probabilistic: bool,
+    opening_time: i64,
+    min_seed_per_outcome: u64,
+    sequence: u64,
+    insured_basis_points: u16,
+    price_reference_account: Option<Pubkey>,
+    creator_seed_fee_exempt: bool,
+    settlement_mint: Pubkey,
+    mint_decimals: u8,
+    oracle_signature_scheme: OracleSignatureScheme,
+    burn_basis_points: u16,
+    privacy_enabled: bool,
+    keeper_tip_basis_points: u16,
+    max_liquidity: u64,
+    community_resolved: bool,
+    community_quorum_basis_points: u16,
+    community_vote_window_seconds: u64,
+    allow_early_resolution: bool,
+    outcome_count: u8,
+    outcome_schema: Pubkey,
+}
+
+/// Write the initial state of a freshly created market
+fn initialize_market(market: &mut Market, params: MarketParams, now: i64) {
+    market.id = params.id;
+    market.vault = params.vault;
+    market.creator = params.creator;
+    market.oracle = params.oracle;
+    market.resolution_time = params.resolution_time;
+    market.commit_reveal_enabled = params.commitment_hash.is_some();
+    market.commitment_hash = params.commitment_hash.unwrap_or([0u8; 32]);
+    market.total_yes_amount = 0;
+    market.total_no_amount = 0;
+    market.outcome_count = params.outcome_count;
+    market.outcome_schema = params.outcome_schema;
+    market.outcome_totals = [0; MAX_OUTCOMES];
+    market.outcome_shares = [0; 2];
+    market.is_resolved = false;
+    market.winning_outcome = None;
+    market.winning_index = None;
+    market.creation_timestamp = now;
+    market.liquidity_locked = 0;
+    market.fee_basis_points = params.fee_basis_points;
+    market.fee_curve = params.fee_curve;
+    market.fee_curve_max_basis_points = params.fee_curve_max_basis_points;
+    market.min_bet_amount = params.min_bet_amount;
+    market.total_claimable = 0;
+    market.total_claimed = 0;
+    market.safe_mode = false;
+    market.safe_mode_available = 0;
+    market.is_probabilistic = params.is_probabilistic;
+    market.resolved_yes_probability = 0;
+    market.opening_time = params.opening_time;
+    // Zero disables seeding, making the market tradable immediately
+    market.min_seed_per_outcome = params.min_seed_per_outcome;
+    market.is_tradable = params.min_seed_per_outcome == 0;
+    market.source_ref = [0u8; 32];
+    market.sequence = params.sequence;
+    market.insured_basis_points = params.insured_basis_points;
+    market.insurance_pool = 0;
+    market.price_reference_account = params.price_reference_account;
+    market.extension_count = 0;
+    // Creator resolving their own market; surfaced so frontends can warn bettors
+    market.self_oracle = params.creator == params.oracle;
+    market.creator_seed_fee_exempt = params.creator_seed_fee_exempt;
+    market.creator_seed_used = false;
+    market.settlement_mint = params.settlement_mint;
+    market.mint_decimals = params.mint_decimals;
+    market.oracle_signature_scheme = params.oracle_signature_scheme;
+    market.community_resolved = params.community_resolved;
+    market.allow_early_resolution = params.allow_early_resolution;
+    market.community_quorum_basis_points = params.community_quorum_basis_points;
+    market.community_vote_window_seconds = params.community_vote_window_seconds;
+    market.resolution_votes = [0; MAX_OUTCOMES];
+    market.is_void = false;
+    market.is_cancelled = false;
+    // Every creation path counts the market in its creator's `CreatorStats`
+    market.holds_creator_slot = true;
+    market.bet_count = 0;
+    reset_settlement_progress(market);
+    market.claims_unlock_time = 0;
+    market.resolution_finalizes_at = 0;
+    market.is_disputed = false;
+    market.disputer = Pubkey::default();
+    market.dispute_bond = 0;
+    market.claim_deadline = 0;
+    market.unclaimed_swept = 0;
+    market.settlement_generation = 0;
+    market.burn_basis_points = params.burn_basis_points;
+    market.burned_amount = 0;
+    market.privacy_enabled = params.privacy_enabled;
+    market.keeper_tip_basis_points = params.keeper_tip_basis_points;
+    market.keeper_pool = 0;
+    market.max_liquidity = params.max_liquidity;
+    market.survey_yes_votes = 0;
+    market.survey_no_votes = 0;
+    market.survey_abstain_votes = 0;
+    market.total_extension_seconds = 0;
+    market.total_lp_tokens = 0;
+    market.lp_settlement_liquidity = 0;
+    market.lp_settlement_supply = 0;
+    market.lp_liquidity_withdrawn = 0;
+    market.lp_mint = Pubkey::default();
+
+    // Initialize through the same LMSR helper used on every bet so the first bet never
+    // jumps the price. A new market has no liquidity (b = liquidity_locked = 0), which
+    // prices both empty pools at exactly 50%.
+    refresh_implied_probability(market);
+}
+
+/// Reject liquidity that would take a market past its `max_liquidity` (zero is unlimited)
+fn require_liquidity_within_cap(market: &Market, amount: u64) -> Result<()> {
+    if market.max_liquidity == 0 {
+        return Ok(());
+    }
+    let liquidity = market
+        .liquidity_locked
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    require!(
+        liquidity <= market.max_liquidity,
+        ErrorCode::LiquidityCapReached
+    );
+    Ok(())
+}
+
+/// Reprice a market from its share quantities and liquidity. Liquidity is the LMSR `b`, so
+/// this runs after every bet and every liquidity change to keep the stored price on the curve.
+fn refresh_implied_probability(market: &mut Market) {
+    // LMSR prices Yes/No only; a multi-outcome market quotes its first outcome's pool share
+    let probability = if market.outcome_count == 2 {
+        calculate_lmsr_probability(
+            market.outcome_shares[Outcome::Yes.index()],
+            market.outcome_shares[Outcome::No.index()],
+            market.liquidity_locked,
+        )
+    } else {
+        calculate_initial_probability(market_pools(market), 0)
+    };
+    market.implied_probability = clamp_probability(probability);
+}
+
+fn validate_template_params(
+    default_duration: u64,
+    fee_basis_points: u16,
+    min_bet_amount: u64,
+    max_fee_basis_points: u16,
+) -> Result<()> {
+    require!(default_duration > 0, ErrorCode::InvalidResolutionTime);
+    require!(fee_basis_points <= max_fee_basis_points, ErrorCode::InvalidFee);
+    require!(min_bet_amount > 0, ErrorCode::InvalidMinBet);
+    Ok(())
+}
+
+/// Current unix timestamp from the Clock sysvar
+#[cfg(not(feature = "test-clock"))]
+fn current_timestamp() -> Result<i64> {
+    Ok(Clock::get()?.unix_timestamp)
+}
+
+/// Current unix timestamp, overridable via `set_mock_timestamp` in test-clock builds
+#[cfg(feature = "test-clock")]
+fn current_timestamp() -> Result<i64> {
+    match MOCK_TIMESTAMP.with(|t| t.get()) {
+        Some(timestamp) => Ok(timestamp),
+        None => Ok(Clock::get()?.unix_timestamp),
+    }
+}
+
+#[cfg(feature = "test-clock")]
+thread_local! {
+    static MOCK_TIMESTAMP: std::cell::Cell<Option<i64>> = std::cell::Cell::new(None);
+}
+
+/// Pin (or with `None`, release) the timestamp returned by `current_timestamp`
+#[cfg(feature = "test-clock")]
+pub fn set_mock_timestamp(timestamp: Option<i64>) {
+    MOCK_TIMESTAMP.with(|t| t.set(timestamp));
+}
+
+/// Offset a unix timestamp by an unsigned number of seconds, failing instead of
+/// wrapping when the duration does not fit in an `i64` or the sum overflows
+fn checked_add_seconds(timestamp: i64, seconds: u64) -> Result<i64> {
+    let seconds = i64::try_from(seconds).map_err(|_| ErrorCode::MathOverflow)?;
+    Ok(timestamp
+        .checked_add(seconds)
+        .ok_or(ErrorCode::MathOverflow)?)
+}
+
+fn calculate_initial_probability(pools: &[u64], outcome_index: usize) -> u64 {
+    let total: u128 = pools.iter().map(|&pool| pool as u128).sum();
+    if total == 0 {
+        return 10_000 / pools.len().max(1) as u64; // Even split, 50% for Yes/No
+    }
+    (pools[outcome_index] as u128 * 10_000 / total) as u64
+}
+
+/// Accounts touched when a bet is booked into a market
+struct BetAccounts<'a, 'info> {
+    vault: &'a mut Account<'info, Vault>,
+    market: &'a mut Account<'info, Market>,
+    bet_account: &'a mut Account<'info, BetAccount>,
+    bettor: &'a Signer<'info>,
+    bettor_token_account: &'a Account<'info, TokenAccount>,
+    vault_token_account: &'a Account<'info, TokenAccount>,
+    token_program: &'a Program<'info, Token>,
+}
+
+/// Verify the bettor's allowlist proof when the vault has an allowlist. Without one the proof
+/// is ignored beyond its length bound and no verification work is done.
+fn require_allowlisted(
+    vault: &Vault,
+    market: &Account<Market>,
+    bettor: &Pubkey,
+    proof: &[u8],
+) -> Result<()> {
+    require_proof_depth(proof, vault.max_merkle_depth)?;
+    if !vault.allowlist_enabled {
+        return Ok(());
+    }
+    verify_merkle_proof(proof, vault.merkle_root, *bettor)
+        .map_err(|e| log_verification_failure("merkle_proof", &market.key(), e))
+}
+
+/// Check the bettor against the vault allowlist and, on privacy-enabled markets, consume
+/// the bet's nullifier. Returns the nullifier to record; public markets record none.
+fn admit_bettor(
+    vault: &Vault,
+    market: &Account<Market>,
+    bettor: &Pubkey,
+    proof: &[u8],
+    nullifier: [u8; 32],
+    nullifier_secret: &[u8; 32],
+    nullifier_account: Option<&mut Account<NullifierAccount>>,
+    nullifier_bump: u8,
+) -> Result<[u8; 32]> {
+    require_allowlisted(vault, market, bettor, proof)?;
+
+    // Public markets skip the nullifier machinery entirely and record no nullifier
+    if !market.privacy_enabled {
+        return Ok([0u8; 32]);
+    }
+
+    // Nullifiers are scoped to a single market: nullifier = keccak(secret || market_id)
+    require!(
+        nullifier == derive_nullifier(nullifier_secret, &market.id),
+        ErrorCode::InvalidNullifier
+    );
+
+    // Verify nullifier hasn't been used (prevent double-spending). The account is a PDA of
+    // the nullifier, so a reused nullifier already fails at `init`; the blank check also
+    // catches an account pre-created outside a bet.
+    let nullifier_account = nullifier_account.ok_or(ErrorCode::NullifierAccountRequired)?;
+    require!(
+        !nullifier_account.is_used && nullifier_account.nullifier == [0u8; 32],
+        ErrorCode::NullifierAlreadyUsed
+    );
+    nullifier_account.is_used = true;
+    nullifier_account.nullifier = nullifier;
+    nullifier_account.bump = nullifier_bump;
+    Ok(nullifier)
+}
+
+/// A bet account about to be written must be blank. `init` guarantees this today; the check
+/// keeps any future flow that reuses accounts from overwriting a live bet.
+fn require_pristine_bet(bet: &BetAccount) -> Result<()> {
+    require!(
+        bet.market == Pubkey::default() && bet.amount == 0 && !bet.is_claimed,
+        ErrorCode::BetAccountInUse
+    );
+    Ok(())
+}
+
+/// Reject bets outside the market's open window or below its minimum. A creator seed
+/// bet is the only bet accepted before `opening_time`.
+fn validate_bet_window(
+    market: &Market,
+    amount: u64,
+    now: i64,
+    is_creator_seed: bool,
+) -> Result<()> {
+    // Verify betting is still open
+    require!(!market.is_resolved, ErrorCode::MarketResolved);
+    require!(
+        is_creator_seed || now >= market.opening_time,
+        ErrorCode::MarketNotOpenYet
+    );
+    require!(
+        now < market.resolution_time,
+        ErrorCode::MarketClosed
+    );
+    require!(amount >= market.min_bet_amount, ErrorCode::BetTooSmall);
+
+    Ok(())
+}
+
+/// Minimum bet on `outcome` at the market's current odds. Past the vault's threshold the
+/// market minimum grows as `(10000 - threshold) / (10000 - p)` for the side's implied
+/// probability `p`, so near-certain sides cannot be bought for dust, up to the vault's cap.
+fn odds_scaled_min_bet(vault: &Vault, market: &Market, outcome: Outcome) -> u64 {
+    let threshold = vault.odds_scaled_min_threshold_bps as u64;
+    let side_probability = match outcome {
+        Outcome::Yes => market.implied_probability,
+        Outcome::No => 10_000 - market.implied_probability,
+    };
+    if threshold == 0 || side_probability <= threshold {
+        return market.min_bet_amount;
+    }
+    let scaled = market.min_bet_amount as u128 * (10_000 - threshold) as u128
+        / (10_000 - side_probability).max(1) as u128;
+    let cap = market.min_bet_amount as u128 * vault.odds_scaled_min_max_multiplier as u128;
+    scaled.min(cap).min(u64::MAX as u128) as u64
+}
+
+/// Reject a bet whose fee and tip leave no stake in the pool, or whose delivered amount
+/// fell below the market minimum in transfer
+fn require_viable_stake(market: &Market, received: u64, stake: u64) -> Result<()> {
+    require!(
+        stake > 0 && received >= market.min_bet_amount,
+        ErrorCode::BetTooSmall
+    );
+    Ok(())
+}
+
+/// Fee rate a bet placed at `now` pays. Under a linear curve the rate rises from the base
+/// fee at creation to the curve maximum at `resolution_time`, capped at `MAX_FEE_BPS`.
+fn effective_fee_basis_points(market: &Market, now: i64) -> u16 {
+    match market.fee_curve {
+        FeeCurve::Fixed => market.fee_basis_points,
+        FeeCurve::Linear => {
+            let window = (market.resolution_time - market.creation_timestamp).max(1) as u128;
+            let elapsed = (now - market.creation_timestamp).clamp(0, window as i64) as u128;
+            let spread = market
+                .fee_curve_max_basis_points
+                .saturating_sub(market.fee_basis_points) as u128;
+            let rise = (spread * elapsed / window) as u16;
+            market.fee_basis_points.saturating_add(rise).min(MAX_FEE_BPS)
+        }
+    }
+}
+
+/// Split a bet into `(fee, keeper_tip, stake)` at `fee_basis_points`. The tip is taken from
+/// the amount net of the fee, so the parts always sum to `amount`. Exempt bets pay neither.
+fn split_bet_amount(
+    amount: u64,
+    market: &Market,
+    fee_basis_points: u16,
+    exempt: bool,
+) -> Result<(u64, u64, u64)> {
+    if exempt {
+        return Ok((0, 0, amount));
+    }
+    let fee = checked_mul_div(amount as u128, fee_basis_points as u128, 10_000)?;
+    let net = amount.checked_sub(fee).ok_or(ErrorCode::MathOverflow)?;
+    let tip = checked_mul_div(net as u128, market.keeper_tip_basis_points as u128, 10_000)?;
+    let stake = net.checked_sub(tip).ok_or(ErrorCode::MathOverflow)?;
+    Ok((fee, tip, stake))
+}
+
+/// Whether a stake on `outcome_index` shrinks the gap between the two pools
+fn is_balancing_bet(pools: &[u64; 2], outcome_index: usize, stake: u64) -> bool {
+    let side = pools[outcome_index] as u128;
+    let other = pools[1 - outcome_index] as u128;
+    let after = side + stake as u128;
+    side < other && after.abs_diff(other) < other - side
+}
+
+/// Book collected fees. Fees and donations are denominated in the vault's own mint;
+/// fees of markets settling in an extra mint are tracked per mint instead.
+fn accrue_fees(vault: &mut Vault, settlement_mint: &Pubkey, fee_amount: u64) -> Result<()> {
+    if *settlement_mint == vault.settlement_mint {
+        let donation_amount =
+            checked_mul_div(fee_amount as u128, vault.donation_basis_points as u128, 10_000)?;
+        let retained = fee_amount
+            .checked_sub(donation_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        vault.total_fees_collected = vault
+            .total_fees_collected
+            .checked_add(retained)
+            .ok_or(ErrorCode::MathOverflow)?;
+        vault.pending_donations = vault
+            .pending_donations
+            .checked_add(donation_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+    } else {
+        let slot = extra_settlement_mint_slot(vault, settlement_mint)
+            .ok_or(ErrorCode::UnsupportedSettlementMint)?;
+        vault.extra_settlement_fees_collected[slot] = vault.extra_settlement_fees_collected
+            [slot]
+            .checked_add(fee_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+    }
+    Ok(())
+}
+
+/// Claim-time fee on a payout: on the whole gross payout, or only on the profit over the
+/// stake. Settlement books the gross payout; the fee stays in the vault as collected fees.
+fn calculate_winnings_fee(
+    gross_winnings: u64,
+    stake: u64,
+    fee_basis_points: u16,
+    basis: WinningsFeeBasis,
+) -> Result<u64> {
+    let fee_base = match basis {
+        WinningsFeeBasis::Gross => gross_winnings,
+        WinningsFeeBasis::Profit => gross_winnings.saturating_sub(stake),
+    };
+    checked_mul_div(fee_base as u128, fee_basis_points as u128, 10_000)
+}
+
+/// Compute and book the vault's winnings fee for one claim, returning the fee taken. A
+/// no-contest refund wins nothing, so like its placement fee it owes no winnings fee.
+fn take_winnings_fee(
+    vault: &mut Vault,
+    market: &Market,
+    gross_winnings: u64,
+    stake: u64,
+) -> Result<u64> {
+    if is_no_contest(market) {
+        return Ok(0);
+    }
+    let fee = calculate_winnings_fee(
+        gross_winnings,
+        stake,
+        vault.winnings_fee_basis_points,
+        vault.winnings_fee_basis,
+    )?;
+    accrue_fees(vault, &market.settlement_mint, fee)?;
+    Ok(fee)
+}
+
+/// Amount actually paid out on a claim: the gross payout less the winnings fee, plus any
+/// placement fee refunded by a void market
+fn net_winnings(gross_winnings: u64, fee_taken: u64, fee_refunded: u64) -> Result<u64> {
+    Ok(gross_winnings
+        .checked_sub(fee_taken)
+        .and_then(|net| net.checked_add(fee_refunded))
+        .ok_or(ErrorCode::MathOverflow)?)
+}
+
+/// Whether a settled market is a no-contest: voided by its community, or resolved to an
+/// outcome nobody backed. Every bet then reclaims exactly its stake, whichever side it took.
+fn is_no_contest(market: &Market) -> bool {
+    market.is_void
+        || !market.is_probabilistic
+            && market
+                .winning_index
+                .map_or(false, |winning| market_pools(market)[winning as usize] == 0)
+}
+
+/// Return a bet's placement fee when its market is voided (nobody backed the winner), since
+/// no service was rendered. The fee comes back out of the collected fees it was booked to;
+/// any donation share already pledged from it stays pledged. A voided or cancelled market
+/// never paid its keeper pool, so the bet's keeper tip comes back out of that pool too.
+fn refund_bet_fee(vault: &mut Vault, market: &mut Market, bet: &BetAccount) -> Result<u64> {
+    if !is_no_contest(market) {
+        return Ok(0);
+    }
+    let keeper_tip = if market.is_void { bet.keeper_tip } else { 0 };
+    market.keeper_pool = market
+        .keeper_pool
+        .checked_sub(keeper_tip)
+        .ok_or(ErrorCode::MathOverflow)?;
+    if bet.fee_amount == 0 {
+        return Ok(keeper_tip);
+    }
+
+    if market.settlement_mint == vault.settlement_mint {
+        vault.total_fees_collected = vault
+            .total_fees_collected
+            .checked_sub(bet.fee_amount)
+            .ok_or(ErrorCode::InsufficientFeesForRefund)?;
+        // Fees already withdrawn have left the vault and cannot fund a refund
+        require!(
+            vault.total_fees_collected >= vault.total_fees_withdrawn,
+            ErrorCode::InsufficientFeesForRefund
+        );
+    } else {
+        let slot = extra_settlement_mint_slot(vault, &market.settlement_mint)
+            .ok_or(ErrorCode::UnsupportedSettlementMint)?;
+        vault.extra_settlement_fees_collected[slot] = vault.extra_settlement_fees_collected
+            [slot]
+            .checked_sub(bet.fee_amount)
+            .ok_or(ErrorCode::InsufficientFeesForRefund)?;
+    }
+    Ok(bet
+        .fee_amount
+        .checked_add(keeper_tip)
+        .ok_or(ErrorCode::MathOverflow)?)
+}
+
+/// Emit the survey tally of a market that received any survey votes
+fn emit_survey_results(market: &Account<Market>, now: i64) {
+    let total_votes =
+        market.survey_yes_votes + market.survey_no_votes + market.survey_abstain_votes;
+    if total_votes > 0 {
+        emit!(SurveyResults {
+            market: market.key(),
+            yes_votes: market.survey_yes_votes,
+            no_votes: market.survey_no_votes,
+            abstain_votes: market.survey_abstain_votes,
+            timestamp: now,
+        });
+    }
+}
+
+/// How a bet of `amount` received tokens splits and what it buys
+struct BetPricing {
+    /// Fee rate charged, before any balancing rebate; zero for fee-exempt bets
+    fee_basis_points: u16,
+    fee_amount: u64,
+    keeper_tip: u64,
+    stake: u64,
+    shares: u64,
+}
+
+/// Price a bet without touching state: min-bet, fee, keeper tip, balancing rebate and the
+/// LMSR shares it buys. `book_bet` and `quote_bet` both go through here, so a quote is
+/// exactly what an identical bet would book.
+fn price_bet(
+    vault: &Vault,
+    market: &Market,
+    amount: u64,
+    outcome_index: u8,
+    fee_exempt: bool,
+    now: i64,
+) -> Result<BetPricing> {
+    require_valid_outcome(market_pools(market), outcome_index)?;
+    // Odds scaling, balancing rebates and LMSR pricing are defined over Yes/No only
+    let min_bet = binary_outcome(market, outcome_index)?.map_or(market.min_bet_amount, |outcome| {
+        odds_scaled_min_bet(vault, market, outcome)
+    });
+    require!(amount >= min_bet, ErrorCode::BetTooSmall);
+
+    // Calculate fees and the keeper tip
+    let fee_basis_points = if fee_exempt {
+        0
+    } else {
+        effective_fee_basis_points(market, now)
+    };
+    let (fee_amount, keeper_tip, bet_amount) =
+        split_bet_amount(amount, market, fee_basis_points, fee_exempt)?;
+    // A bet that pulls the pools toward 50/50 gets part of its fee back as extra stake
+    let rebate = match binary_outcome {
+        Some(outcome) if is_balancing_bet(&outcome_pools(market), outcome.index(), bet_amount) => {
+            checked_mul_div(
+                fee_amount as u128,
+                vault.balancing_rebate_basis_points as u128,
+                10_000,
+            )?
+        }
+        _ => 0,
+    };
+    let fee_amount = fee_amount.checked_sub(rebate).ok_or(ErrorCode::MathOverflow)?;
+    let bet_amount = bet_amount.checked_add(rebate).ok_or(ErrorCode::MathOverflow)?;
+    require_viable_stake(market, amount, bet_amount)?;
+
+    // Until every side is seeded, only a qualifying first bet on an unseeded side is accepted
+    if !market.is_tradable {
+        require!(
+            market_pools(market)[outcome_index as usize] == 0
+                && bet_amount >= market.min_seed_per_outcome,
+            ErrorCode::MarketNotTradable
+        );
+    }
+
+    // Buy outcome shares on the LMSR cost curve; the stake is the price paid for them
+    let shares = match binary_outcome {
+        Some(outcome) => {
+            calculate_lmsr_trade(
+                market.outcome_shares,
+                outcome,
+                bet_amount,
+                market.liquidity_locked,
+            )?
+            .shares
+        }
+        None => bet_amount,
+    };
+
+    Ok(BetPricing {
+        fee_basis_points,
+        fee_amount,
+        keeper_tip,
+        stake: bet_amount,
+        shares,
+    })
+}
+
+/// Price a bet on a copy of the market and settle the copy as if the quoted outcome won
+/// outright, for `quote_bet`
+fn project_bet(
+    vault: &Vault,
+    market: &Market,
+    amount: u64,
+    outcome_index: u8,
+    now: i64,
+) -> Result<BetQuote> {
+    let pricing = price_bet(vault, market, amount, outcome_index, false, now)?;
+    let mut projected = Market::clone(market);
+    apply_bet(&mut projected, outcome_index, &pricing)?;
+
+    projected.burned_amount = calculate_resolution_burn(
+        market_pools(&projected),
+        outcome_index as usize,
+        projected.burn_basis_points,
+    )?;
+    projected.winning_index = Some(outcome_index);
+    projected.resolved_yes_probability = if outcome_index == Outcome::Yes.index() as u8 {
+        10_000
+    } else {
+        0
+    };
+    let payout = stake_entitlement(&projected, pricing.stake, outcome_index)?.unwrap_or(0);
+
+    Ok(BetQuote {
+        fee_basis_points: pricing.fee_basis_points,
+        fee_amount: pricing.fee_amount,
+        keeper_tip: pricing.keeper_tip,
+        stake: pricing.stake,
+        shares: pricing.shares,
+        implied_probability: projected.implied_probability,
+        payout,
+    })
+}
+
+/// Book a priced bet into the market's pools, share quantities and implied probability
+fn apply_bet(market: &mut Market, outcome_index: u8, pricing: &BetPricing) -> Result<()> {
+    market.keeper_pool = market
+        .keeper_pool
+        .checked_add(pricing.keeper_tip)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let pool = &mut market.outcome_totals[outcome_index as usize];
+    *pool = pool.checked_add(pricing.stake).ok_or(ErrorCode::MathOverflow)?;
+    // The Yes/No totals read by binary pricing mirror the first two outcome pools
+    market.total_yes_amount = market.outcome_totals[Outcome::Yes.index()];
+    market.total_no_amount = market.outcome_totals[Outcome::No.index()];
+    if !market.is_tradable {
+        market.is_tradable = market_pools(market)
+            .iter()
+            .all(|&pool| pool >= market.min_seed_per_outcome);
+    }
+
+    if market.outcome_count == 2 {
+        let quantity = &mut market.outcome_shares[outcome_index as usize];
+        *quantity = quantity.checked_add(pricing.shares).ok_or(ErrorCode::MathOverflow)?;
+    }
+
+    // Update implied probability using LMSR (Logarithmic Market Scoring Rule)
+    refresh_implied_probability(market);
+    Ok(())
+}
+
+/// Collect the stake and fee, credit the pool and record the bet
+fn book_bet(
+    accounts: BetAccounts,
+    amount: u64,
+    outcome_index: u8,
+    nullifier: [u8; 32],
+    memo: [u8; 16],
+    fee_exempt: bool,
+    now: i64,
+) -> Result<()> {
+    let vault = accounts.vault;
+    let market = accounts.market;
+    require!(market.vault == vault.key(), ErrorCode::InvalidMarketVault);
+    require_inflows_open(vault)?;
+    require_pristine_bet(accounts.bet_account)?;
+    require_vault_token_account(accounts.vault_token_account, &vault.key())?;
+    require_settlement_mint(
+        market,
+        &[accounts.bettor_token_account, accounts.vault_token_account],
+    )?;
+    require_valid_outcome(market_pools(market), outcome_index)?;
+
+    // Pull the tokens in before any state is written, so a transfer hook reading the market
+    // sees it untouched, and book only what actually arrived: fee-on-transfer or hook mints
+    // can deliver less than requested. A failed check below still reverts the transfer.
+    let amount = receive_into_vault(
+        accounts.bettor_token_account,
+        accounts.vault_token_account,
+        accounts.bettor.to_account_info(),
+        accounts.token_program,
+        amount,
+    )?;
+    let pricing = price_bet(vault, market, amount, outcome_index, fee_exempt, now)?;
+
+    // Update market state
+    let pooled_before: u128 = market_pools(market).iter().map(|&pool| pool as u128).sum();
+    apply_bet(market, outcome_index, &pricing)?;
+
+    // Insured obligations of whichever side loses must stay covered by the insurance deposit
+    if market.insured_basis_points > 0 {
+        let max_side = outcome_pools(market).iter().copied().max().unwrap_or(0);
+        require!(
+            calculate_insured_refund(max_side, market.insured_basis_points)?
+                <= market.insurance_pool,
+            ErrorCode::InsufficientInsurance
+        );
+    }
+
+    // Record bet
+    let bet_account = accounts.bet_account;
+    bet_account.market = market.key();
+    bet_account.bettor = accounts.bettor.key();
+    bet_account.amount = pricing.stake;
+    bet_account.outcome = binary_outcome(market, outcome_index)?;
+    bet_account.outcome_index = outcome_index;
+    bet_account.shares = pricing.shares;
+    bet_account.timestamp = now;
+    bet_account.odds = market.implied_probability;
+    bet_account.nullifier = nullifier;
+    bet_account.is_claimed = false;
+    // All-zero memo means no attribution tag
+    bet_account.memo = memo;
+    bet_account.vault = vault.key();
+    bet_account.sequence = vault.bet_count;
+    bet_account.fee_amount = pricing.fee_amount;
+    bet_account.keeper_tip = pricing.keeper_tip;
+    bet_account.fee_basis_points = pricing.fee_basis_points;
+    bet_account.settlement_generation = market.settlement_generation;
+    bet_account.resolution_voted = false;
+    bet_account.market_index = market.bet_count;
+    bet_account.ownership_transferred = false;
+    vault.bet_count = vault.bet_count.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    market.bet_count = market.bet_count.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+
+    // The recorded stake must be exactly what was credited to the pools
+    let pooled_after: u128 = market_pools(market).iter().map(|&pool| pool as u128).sum();
+    require!(
+        pooled_after.checked_sub(pooled_before) == Some(bet_account.amount as u128),
+        ErrorCode::PoolConservationViolated
+    );
+
+    // Update vault statistics. Volume is denominated in the vault's own mint.
+    if market.settlement_mint == vault.settlement_mint {
+        vault.total_volume = vault
+            .total_volume
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        bet_account.volume_amount = amount;
+    } else {
+        bet_account.volume_amount = 0;
+    }
+    accrue_fees(vault, &market.settlement_mint, pricing.fee_amount)?;
+
+    emit!(BetPlaced {
+        market: market.key(),
+        bettor: accounts.bettor.key(),
+        bet_id: derive_bet_id(&market.key(), &accounts.bettor.key(), &nullifier),
+        nullifier,
+        amount: pricing.stake,
+        outcome: bet_account.outcome,
+        outcome_index,
+        odds: market.implied_probability,
+        memo,
+        mint_decimals: market.mint_decimals,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Debug-only conservation check: the recorded bet amounts of a market must sum to its pools
+#[cfg(any(test, feature = "debug"))]
+pub fn verify_pool_conservation(market: &Market, bets: &[BetAccount]) -> Result<()> {
+    let recorded: u128 = bets.iter().map(|bet| bet.amount as u128).sum();
+    let pooled: u128 = market_pools(market).iter().map(|&pool| pool as u128).sum();
+    require!(recorded == pooled, ErrorCode::PoolConservationViolated);
+    Ok(())
+}
+
+/// Marginal Yes price of the LMSR cost function `C(q) = b * ln(exp(q_yes / b) + exp(q_no / b))`
+/// at share quantities `yes` / `no`, in basis points.
+///
+/// Markets without LP liquidity run in pure parimutuel mode: the LMSR curve is
+/// undefined without a liquidity parameter, so the price is the pool ratio instead.
+/// Settlement never depends on liquidity, so create -> bet -> resolve -> claim works
+/// without any call to `add_liquidity`.
+fn calculate_lmsr_probability(yes: u64, no: u64, liquidity: u64) -> u64 {
+    if liquidity == 0 {
+        return calculate_initial_probability(&[yes, no], Outcome::Yes.index());
+    }
+
+    // Logistic form of exp(yes/b) / (exp(yes/b) + exp(no/b)): only the gap between the
+    // quantities is exponentiated, and an infinite exponent still yields a price of 0
+    let gap = (no as f64 - yes as f64) / liquidity as f64;
+    probability_to_bps(1.0 / (1.0 + gap.exp()))
+}
+
+/// Outcome shares bought on the LMSR curve and the Yes price they leave the market at
+struct LmsrTrade {
+    shares: u64,
+    yes_probability: u64,
+}
+
+/// Buy shares of `outcome` for `cost` tokens: the shares `delta` for which
+/// `C(q + delta) - C(q) = cost`. With `t = cost / b` and current price `p`,
+/// `delta = b * ln(1 + (exp(t) - 1) / p)`, evaluated in the log domain so large `q / b` or
+/// `cost / b` ratios cannot overflow. Shares round down, in the market's favour. Without
+/// liquidity there is no curve and each token buys one share.
+fn calculate_lmsr_trade(
+    quantities: [u64; 2],
+    outcome: Outcome,
+    cost: u64,
+    liquidity: u64,
+) -> Result<LmsrTrade> {
+    let shares = if liquidity == 0 {
+        cost
+    } else {
+        let b = liquidity as f64;
+        let own = quantities[outcome.index()] as f64;
+        let other = quantities[1 - outcome.index()] as f64;
+        let t = cost as f64 / b;
+        // ln((exp(t) - 1) / p), using ln(1 / p) = softplus((other - own) / b)
+        let log_ratio = ln_expm1(t) + softplus((other - own) / b);
+        let shares = b * softplus(log_ratio);
+        require!(
+            shares.is_finite() && shares >= 0.0 && shares < u64::MAX as f64,
+            ErrorCode::MathOverflow
+        );
+        shares.floor() as u64
+    };
+
+    let mut after = quantities;
+    after[outcome.index()] = after[outcome.index()]
+        .checked_add(shares)
+        .ok_or(ErrorCode::MathOverflow)?;
+    Ok(LmsrTrade {
+        shares,
+        yes_probability: calculate_lmsr_probability(after[0], after[1], liquidity),
+    })
+}
+
+/// `ln(1 + exp(x))` without overflow for large `x`
+fn softplus(x: f64) -> f64 {
+    x.max(0.0) + (-x.abs()).exp().ln_1p()
+}
+
+/// `ln(exp(t) - 1)` for `t > 0` without overflow for large `t`
+fn ln_expm1(t: f64) -> f64 {
+    if t > 30.0 {
+        t + (-(-t).exp()).ln_1p()
+    } else {
+        t.exp_m1().ln()
+    }
+}
+
+/// Terminal-status guard. A resolved market, or one that has paid out any claim, may
+/// never be resolved again, extended, or refunded; every cancel, void or expiry path
+/// must call this before touching pools so a paid-out market cannot be double-spent.
+fn require_not_settled(market: &Market) -> Result<()> {
+    require!(
+        !market.is_resolved && market.total_claimed == 0,
+        ErrorCode::MarketAlreadyResolved
+    );
+    Ok(())
+}
+
+/// Check the oracle's reveal against the committed hash; markets created without a
+/// commitment skip this and rely on the oracle signature alone
+fn verify_reveal(market: &Market, reveal_value: &[u8; 32]) -> Result<()> {
+    if !market.commit_reveal_enabled {
+        return Ok(());
+    }
+    require!(
+        hashv(&[reveal_value]).to_bytes() == market.commitment_hash,
+        ErrorCode::InvalidReveal
+    );
+    Ok(())
+}
+
+/// Reject Yes/No-only pricing and quoting on a multi-outcome market
+fn require_binary_market(market: &Market) -> Result<()> {
+    require!(market.outcome_count == 2, ErrorCode::BinaryMarketOnly);
+    Ok(())
+}
+
+/// A resolution must name one of the market's own outcomes; every later pool lookup
+/// indexes by it, so an out-of-range index would corrupt settlement
+fn require_valid_outcome(pools: &[u64], outcome_index: u8) -> Result<()> {
+    require!((outcome_index as usize) < pools.len(), ErrorCode::InvalidOutcome);
+    Ok(())
+}
+
+/// Add a bet's stake to its outcome's bucket, keyed on `outcome_index` since
+/// `BetAccount::outcome` is only set in binary markets
+fn add_outcome_stake(staked_by_outcome: &mut [u64], bet: &BetAccount) -> Result<()> {
+    let staked = staked_by_outcome
+        .get_mut(bet.outcome_index as usize)
+        .ok_or(ErrorCode::InvalidOutcome)?;
+    *staked = staked.checked_add(bet.amount).ok_or(ErrorCode::MathOverflow)?;
+    Ok(())
+}
+
+/// A schema labels 2..=`MAX_OUTCOMES` outcomes, each with a distinct non-empty label
+fn validate_outcome_labels(labels: &[[u8; 16]]) -> Result<()> {
+    require!(
+        (2..=MAX_OUTCOMES).contains(&labels.len()),
+        ErrorCode::InvalidOutcomeSchema
+    );
+    for (index, label) in labels.iter().enumerate() {
+        require!(
+            label.iter().any(|&byte| byte != 0) && !labels[..index].contains(label),
+            ErrorCode::InvalidOutcomeSchema
+        );
+    }
+    Ok(())
+}
+
+/// A market may only take a schema registered by its own vault with exactly its outcome
+/// count, so every winning index the resolution paths accept has a label
+fn require_outcome_schema(schema: &OutcomeSchema, vault: &Pubkey, outcome_count: u8) -> Result<()> {
+    require!(
+        schema.vault == *vault && schema.outcome_count == outcome_count,
+        ErrorCode::InvalidOutcomeSchema
+    );
+    Ok(())
+}
+
+/// An early resolution pulls the betting cutoff back to the moment of resolution, so
+/// every `resolution_time` check sees the market as closed from then on
+fn close_betting_early(market: &mut Market, now: i64) {
+    if now < market.resolution_time {
+        market.resolution_time = now;
+    }
+}
+
+/// Phase of a market at `now`. Only creation, extension and settlement are transactions;
+/// opening and closing happen on the clock, so the phase is derived rather than stored.
+fn market_phase(market: &Market, now: i64) -> MarketPhase {
+    if market.is_void {
+        MarketPhase::Void
+    } else if market.is_resolved {
+        MarketPhase::Resolved
+    } else if now >= market.resolution_time {
+        if market.community_resolved {
+            MarketPhase::Voting
+        } else {
+            MarketPhase::Closed
+        }
+    } else if now >= market.opening_time {
+        MarketPhase::Open
+    } else {
+        MarketPhase::Pending
+    }
+}
+
+/// Emit `MarketLifecycleEvent` when a transaction moved the market out of `from_phase`
+/// (`None` at creation)
+fn emit_lifecycle_event(
+    market: &Account<Market>,
+    from_phase: Option<MarketPhase>,
+    actor: Pubkey,
+    now: i64,
+) {
+    let to_phase = market_phase(market, now);
+    if from_phase == Some(to_phase) {
+        return;
+    }
+    emit!(MarketLifecycleEvent {
+        market: market.key(),
+        market_id: market.id,
+        from_phase,
+        to_phase,
+        actor,
+        timestamp: now,
+    });
+}
+
+/// Report the vault token balance after a payout next to the market's claim totals, so
+/// solvency monitors can spot drift as soon as it happens
+fn emit_vault_balance_snapshot(
+    market: &Account<Market>,
+    vault_token_account: &Account<TokenAccount>,
+    now: i64,
+) -> Result<()> {
+    emit!(VaultBalanceSnapshot {
+        market: market.key(),
+        vault_balance: token_balance(vault_token_account)?,
+        total_claimed: market.total_claimed,
+        total_claimable: market.total_claimable,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+/// When claims on a market settled at `now` close: `claim_window_seconds` after they unlock
+/// and the challenge window ends, or never (zero) without a claim window
+fn claim_deadline(market: &Market, claim_window_seconds: u64, now: i64) -> Result<i64> {
+    if claim_window_seconds == 0 {
+        return Ok(0);
+    }
+    let unlock = market.claims_unlock_time.max(market.resolution_finalizes_at);
+    checked_add_seconds(unlock.max(now), claim_window_seconds)
+}
+
+/// Record a resolved market's distributable pool and switch to pro-rata payouts when the
+/// vault holds less than that, so every claimant shares the shortfall. With a claim window
+/// configured, claims close that long after they unlock and the challenge window ends.
+fn open_claims(
+    market: &mut Account<Market>,
+    total_claimable: u64,
+    available: u64,
+    claim_window_seconds: u64,
+    now: i64,
+) -> Result<()> {
+    market.total_claimable = total_claimable;
+    market.total_claimed = 0;
+    market.unclaimed_swept = 0;
+    market.safe_mode = available < total_claimable;
+    market.safe_mode_available = available.min(total_claimable);
+    market.claim_deadline = claim_deadline(market, claim_window_seconds, now)?;
+    if market.safe_mode {
+        emit!(SafeModeActivated {
+            market: market.key(),
+            total_claimable,
+            available,
+            timestamp: now,
+        });
+    }
+    Ok(())
+}
+
+/// Part of a market's distributable pool still unclaimed; in safe mode only the vault's
+/// available balance was ever payable
+fn unclaimed_balance(market: &Market) -> u64 {
+    let payable = if market.safe_mode {
+        market.safe_mode_available
+    } else {
+        market.total_claimable
+    };
+    payable.saturating_sub(market.total_claimed)
+}
+
+/// End of a community-resolved market's vote window
+fn community_vote_deadline(market: &Market) -> Result<i64> {
+    checked_add_seconds(market.resolution_time, market.community_vote_window_seconds)
+}
+
+/// Index of the outcome a community vote settles on once turnout reaches the quorum share of
+/// total stake and one outcome holds a strict majority of the votes cast
+fn community_vote_result(market: &Market) -> Option<u8> {
+    let total_stake: u128 = market_pools(market).iter().map(|&pool| pool as u128).sum();
+    let votes = &market.resolution_votes[..market.outcome_count as usize];
+    let cast: u128 = votes.iter().map(|&tally| tally as u128).sum();
+    if cast == 0 || cast * 10_000 < total_stake * market.community_quorum_basis_points as u128 {
+        return None;
+    }
+    votes
+        .iter()
+        .position(|&tally| tally as u128 * 2 > cast)
+        .map(|index| index as u8)
+}
+
+/// Advance the market's settlement generation on every change to its resolution. Any
+/// entitlement tree built against the previous settlement is discarded with it.
+fn bump_settlement_generation(market: &mut Market) -> Result<()> {
+    market.settlement_generation = market
+        .settlement_generation
+        .checked_add(1)
+        .ok_or(ErrorCode::MathOverflow)?;
+    reset_settlement_progress(market);
+    Ok(())
+}
+
+/// Restart the batched settlement pass, discarding any entitlement tree built so far
+fn reset_settlement_progress(market: &mut Market) {
+    market.settlement_cursor = 0;
+    market.settlement_complete = false;
+    market.entitlement_frontier = [[0u8; 32]; ENTITLEMENT_TREE_DEPTH];
+    market.entitlement_root = [0u8; 32];
+}
+
+/// Leaf committing a bet to its entitlement: keccak(bettor || bet || entitlement)
+fn entitlement_leaf(bettor: &Pubkey, bet: &Pubkey, entitlement: u64) -> [u8; 32] {
+    hashv(&[bettor.as_ref(), bet.as_ref(), &entitlement.to_le_bytes()]).to_bytes()
+}
+
+/// Append the leaf at position `count` to an incremental merkle tree's frontier
+fn append_entitlement_leaf(
+    frontier: &mut [[u8; 32]; ENTITLEMENT_TREE_DEPTH],
+    count: u64,
+    leaf: [u8; 32],
+) {
+    let mut node = leaf;
+    for (level, sibling) in frontier.iter_mut().enumerate() {
+        if (count >> level) & 1 == 0 {
+            *sibling = node;
+            return;
+        }
+        node = hashv(&[sibling, &node]).to_bytes();
+    }
+}
+
+/// Root of an incremental merkle tree holding `count` leaves, empty leaves being all zero
+fn entitlement_tree_root(frontier: &[[u8; 32]; ENTITLEMENT_TREE_DEPTH], count: u64) -> [u8; 32] {
+    let mut node = [0u8; 32];
+    let mut zero = [0u8; 32];
+    for (level, sibling) in frontier.iter().enumerate() {
+        node = if (count >> level) & 1 == 1 {
+            hashv(&[sibling, &node]).to_bytes()
+        } else {
+            hashv(&[&node, &zero]).to_bytes()
+        };
+        zero = hashv(&[&zero, &zero]).to_bytes();
+    }
+    node
+}
+
+/// Check a merkle proof (siblings from the leaf upwards) of a bet's entitlement at
+/// `leaf_index` against a committed root
+fn verify_entitlement(
+    root: &[u8; 32],
+    bettor: &Pubkey,
+    bet: &Pubkey,
+    entitlement: u64,
+    leaf_index: u64,
+    proof: &[[u8; 32]],
+) -> bool {
+    if proof.len() != ENTITLEMENT_TREE_DEPTH {
+        return false;
+    }
+    let mut node = entitlement_leaf(bettor, bet, entitlement);
+    for (level, sibling) in proof.iter().enumerate() {
+        node = if (leaf_index >> level) & 1 == 0 {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        };
+    }
+    node == *root
+}
+
+/// A bet is stamped with the generation it was placed under and may only be claimed
+/// against the one resolution that followed it; a market whose settlement changed again
+/// since (e.g. a re-resolution) would otherwise pay it out against the wrong state
+fn require_current_settlement(market: &Market, bet: &BetAccount) -> Result<()> {
+    require!(
+        bet.settlement_generation.checked_add(1) == Some(market.settlement_generation),
+        ErrorCode::StaleSettlement
+    );
+    Ok(())
+}
+
+/// Whether a bet on a resolved market may still be claimed: unclaimed winners, refunds of
+/// a void market, insured losers and every probabilistic holder
+fn bet_has_open_claim(market: &Market, bet: &BetAccount) -> bool {
+    if bet.is_claimed {
+        return false;
+    }
+    if market.is_void || market.is_probabilistic {
+        return true;
+    }
+    match market.winning_index {
+        Some(winning) => {
+            market_pools(market)[winning as usize] == 0
+                || is_winning_outcome(bet.outcome_index, market.winning_index)
+                || market.insured_basis_points > 0
+        }
+        None => true,
+    }
+}
+
+/// A resolved market owes nothing more once claims, keeper tips and LP shares are paid out
+fn market_fully_settled(market: &Market) -> bool {
+    market.is_resolved
+        && market.total_claimed == market.total_claimable
+        && market.keeper_pool == 0
+        && market.lp_liquidity_withdrawn == market.lp_settlement_liquidity
+}
+
+/// Single place where a bet's outcome is compared to a market result. Compares by
+/// outcome index, and an unset result (void or not yet settled) never matches.
+fn is_winning_outcome(bet_index: u8, winning_index: Option<u8>) -> bool {
+    winning_index == Some(bet_index)
+}
+
+/// Gross payout a resolved market owes a bet, before the vault's winnings fee and any
+/// placement-fee refund, or `None` when the bet is owed nothing
+fn bet_entitlement(market: &Market, bet: &BetAccount) -> Result<Option<u64>> {
+    stake_entitlement(market, bet.amount, bet.outcome_index)
+}
+
+/// `bet_entitlement` for a stake on `outcome_index`, so quotes price hypothetical bets
+/// through the same payout rules as claims
+fn stake_entitlement(market: &Market, stake: u64, outcome_index: u8) -> Result<Option<u64>> {
+    let winnings = if market.is_void {
+        stake
+    } else if market.is_probabilistic {
+        calculate_probabilistic_payout(
+            stake,
+            Outcome::from_discriminant(outcome_index)?,
+            &outcome_pools(market),
+            market.resolved_yes_probability,
+        )?
+    } else {
+        let pools = market_pools(market);
+        let winning_index = market.winning_index.ok_or(ErrorCode::MarketNotResolved)? as usize;
+        let is_winner = is_winning_outcome(outcome_index, market.winning_index);
+
+        // Nobody backed the winning outcome: every stake is refunded instead
+        let is_refund = pools[winning_index] == 0;
+        // In insured markets losers reclaim the insured share of their stake
+        let is_insured_loser = !is_refund && !is_winner && market.insured_basis_points > 0;
+        if !(is_refund || is_insured_loser || is_winner) {
+            return Ok(None);
+        }
+
+        // Calculate winnings
+        if is_refund {
+            stake
+        } else if is_insured_loser {
+            calculate_insured_refund(stake, market.insured_basis_points)?
+        } else if market.insured_basis_points > 0 {
+            calculate_insured_winner_payout(
+                stake,
+                pools,
+                winning_index,
+                market.insurance_pool,
+                market.insured_basis_points,
+                market.burned_amount,
+            )?
+        } else {
+            calculate_parimutuel_payout(stake, pools, winning_index, market.burned_amount)?
+        }
+    };
+    let winnings = if market.safe_mode {
+        apply_safe_mode_haircut(winnings, market.safe_mode_available, market.total_claimable)?
+    } else {
+        winnings
+    };
+    Ok(Some(winnings))
+}
+
+/// Only `resolve_market` opens a challenge window, since a dispute can only be upheld by
+/// correcting a single winning outcome: probabilistic splits, community votes and voided or
+/// cancelled markets settle final and are rejected before any bond is posted
+fn require_disputable(market: &Market, now: i64) -> Result<()> {
+    require!(market.is_resolved, ErrorCode::MarketNotResolved);
+    require!(
+        !market.is_probabilistic && !market.community_resolved && market.winning_index.is_some(),
+        ErrorCode::DisputeNotAllowed
+    );
+    require!(!market.is_disputed, ErrorCode::MarketAlreadyDisputed);
+    require!(
+        now < market.resolution_finalizes_at,
+        ErrorCode::DisputeWindowClosed
+    );
+    Ok(())
+}
+
+/// Claims are accepted from the later of the override unlock and the end of an undisputed
+/// challenge window until the claim deadline, if any
+fn require_claims_open(market: &Market, now: i64) -> Result<()> {
+    require!(market.is_resolved, ErrorCode::MarketNotResolved);
+    require!(now >= market.claims_unlock_time, ErrorCode::ClaimsLocked);
+    require!(
+        now >= market.resolution_finalizes_at && !market.is_disputed,
+        ErrorCode::MarketUnderChallenge
+    );
+    require!(
+        market.claim_deadline == 0 || now < market.claim_deadline,
+        ErrorCode::ClaimWindowExpired
+    );
+    Ok(())
+}
+
+/// Validate a claim against a resolved market, compute its payout and book it against
+/// the market's claimable total
+fn settle_claim(
+    market: &mut Market,
+    bet: &BetAccount,
+    claimant: &Pubkey,
+    proof: &[u8],
+) -> Result<u64> {
+    require_claims_open(market, current_timestamp()?)?;
+    require!(!bet.is_claimed, ErrorCode::AlreadyClaimed);
+    require_current_settlement(market, bet)?;
+
+    let winnings = bet_entitlement(market, bet)?.ok_or(ErrorCode::NotWinner)?;
+    verify_claim_ownership(market, bet, claimant, proof)?;
+
+    // Enforce conservation: total paid out never exceeds the recorded claimable pool
+    let total_claimed = market
+        .total_claimed
+        .checked_add(winnings)
+        .ok_or(ErrorCode::ClaimExceedsClaimable)?;
+    require!(
+        total_claimed <= market.total_claimable,
+        ErrorCode::ClaimExceedsClaimable
+    );
+    market.total_claimed = total_claimed;
+
+    Ok(winnings)
+}
+
+/// Only the owner of an open, unclaimed position may transfer it, and only to another
+/// real wallet
+fn require_transferable(
+    market: &Market,
+    bet: &BetAccount,
+    signer: &Pubkey,
+    new_owner: &Pubkey,
+) -> Result<()> {
+    require!(!market.is_resolved, ErrorCode::MarketResolved);
+    require!(bet.bettor == *signer, ErrorCode::Unauthorized);
+    require!(!bet.is_claimed, ErrorCode::AlreadyClaimed);
+    require!(
+        *new_owner != bet.bettor && *new_owner != Pubkey::default(),
+        ErrorCode::InvalidPositionOwner
+    );
+    Ok(())
+}
+
+/// Only the bettor may claim. Private positions that were never transferred must also
+/// prove ownership with a ZK proof over the nullifier
+fn verify_claim_ownership(
+    market: &Market,
+    bet: &BetAccount,
+    claimant: &Pubkey,
+    proof: &[u8],
+) -> Result<()> {
+    require!(*claimant == bet.bettor, ErrorCode::Unauthorized);
+    if market.privacy_enabled && !bet.ownership_transferred {
+        verify_zk_proof(proof, &bet.nullifier, claimant)?;
+    }
+    Ok(())
+}
+
+/// Linearly vested amount of an escrow at `now`
+fn calculate_vested_amount(escrow: &VestingEscrow, now: i64) -> Result<u64> {
+    if now >= escrow.end_time {
+        return Ok(escrow.total_amount);
+    }
+    let elapsed = (now - escrow.start_time).max(0) as u128;
+    let duration = (escrow.end_time - escrow.start_time) as u128;
+    checked_mul_div(escrow.total_amount as u128, elapsed, duration)
+}
+
+/// Sum of the stakes on every outcome
+fn pooled_stakes(market: &Market) -> Result<u64> {
+    Ok(market_pools(market)
+        .iter()
+        .try_fold(0u64, |total, &pool| total.checked_add(pool))
+        .ok_or(ErrorCode::MathOverflow)?)
+}
+
+/// Per-outcome pool totals of any market, indexed by outcome index
+fn market_pools(market: &Market) -> &[u64] {
+    &market.outcome_totals[..market.outcome_count as usize]
+}
+
+/// Yes/No view of an outcome index, defined only for binary markets
+fn binary_outcome(market: &Market, outcome_index: u8) -> Result<Option<Outcome>> {
+    if market.outcome_count == 2 {
+        Ok(Some(Outcome::from_discriminant(outcome_index)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Yes/No pool totals for the binary-only paths, indexed by `Outcome::index`
+fn outcome_pools(market: &Market) -> [u64; 2] {
+    [market.total_yes_amount, market.total_no_amount]
+}
+
+/// `a * b / denominator` through a `u128` intermediate, failing rather than wrapping or
+/// truncating. The product fits whenever `a * b < 2^128`, which holds for any stake with a
+/// total pool below `u64::MAX`; the quotient must itself fit back into a `u64`.
+fn checked_mul_div(a: u128, b: u128, denominator: u128) -> Result<u64> {
+    let product = a.checked_mul(b).ok_or(ErrorCode::MathOverflow)?;
+    let quotient = product
+        .checked_div(denominator)
+        .ok_or(ErrorCode::MathOverflow)?;
+    Ok(u64::try_from(quotient).map_err(|_| ErrorCode::MathOverflow)?)
+}
+
+/// Parimutuel payout: the whole pool, less any burned share of the losing pool, is shared
+/// pro-rata among stakes on the winning index. Callers must route a zero-stake winning
+/// index to the refund path.
+fn calculate_parimutuel_payout(
+    stake: u64,
+    pools: &[u64],
+    winning_index: usize,
+    burned: u64,
+) -> Result<u64> {
+    let total_pool: u128 = pools.iter().map(|&pool| pool as u128).sum();
+    let distributable = total_pool
+        .checked_sub(burned as u128)
+        .ok_or(ErrorCode::MathOverflow)?;
+    checked_mul_div(stake as u128, distributable, pools[winning_index] as u128)
+}
+
+/// Parimutuel payout per unit of winning stake in basis points, after any burn.
+/// Callers must handle a zero winning pool as a void market.
+fn calculate_winning_payout_ratio(pools: &[u64], winning_index: usize, burned: u64) -> Result<u64> {
+    let total_pool: u128 = pools.iter().map(|&pool| pool as u128).sum();
+    let distributable = total_pool
+        .checked_sub(burned as u128)
+        .ok_or(ErrorCode::MathOverflow)?;
+    checked_mul_div(distributable, 10_000, pools[winning_index] as u128)
+}
+
+/// Burn taken when `winning_index` wins; a refund (no winners) burns nothing
+fn calculate_resolution_burn(
+    pools: &[u64],
+    winning_index: usize,
+    burn_basis_points: u16,
+) -> Result<u64> {
+    if pools[winning_index] == 0 {
+        return Ok(0);
+    }
+    calculate_loser_burn(pools, winning_index, burn_basis_points)
+}
+
+/// Share of the losing pool burned at resolution
+fn calculate_loser_burn(
+    pools: &[u64],
+    winning_index: usize,
+    burn_basis_points: u16,
+) -> Result<u64> {
+    let total_pool: u128 = pools.iter().map(|&pool| pool as u128).sum();
+    let losing_pool = total_pool - pools[winning_index] as u128;
+    checked_mul_div(losing_pool, burn_basis_points as u128, 10_000)
+}
+
+/// Probabilistic payout: each side receives `total_pool * p` (Yes) or `total_pool * (1 - p)` (No),
+/// shared pro-rata by stake; a side with no stake forfeits its share to the other side
+fn calculate_probabilistic_payout(
+    stake: u64,
+    outcome: Outcome,
+    pools: &[u64; 2],
+    yes_probability: u64,
+) -> Result<u64> {
+    let [yes_pool, no_pool] = *pools;
+    let total_pool = yes_pool as u128 + no_pool as u128;
+    let yes_share = match (yes_pool, no_pool) {
+        (0, _) => 0,
+        (_, 0) => total_pool,
+        _ => total_pool * yes_probability as u128 / 10_000,
+    };
+    let (side_share, side_pool) = match outcome {
+        Outcome::Yes => (yes_share, yes_pool),
+        Outcome::No => (total_pool - yes_share, no_pool),
+    };
+    if side_pool == 0 {
+        return Ok(0);
+    }
+    checked_mul_div(stake as u128, side_share, side_pool as u128)
+}
+
+/// Check a vault's base fee against the config ceiling and resolve its curve maximum. A
+/// fixed fee is bounded by the ceiling alone, as before curves existed; only a linear curve
+/// must rise from the base fee to a maximum within both the ceiling and `MAX_FEE_BPS`.
+fn validate_fee_curve(
+    fee_basis_points: u16,
+    max_fee_basis_points: Option<u16>,
+    fee_curve: FeeCurve,
+    config: &ProgramConfig,
+) -> Result<(FeeCurve, u16)> {
+    require!(
+        fee_basis_points <= config.max_fee_basis_points,
+        ErrorCode::InvalidFee
+    );
+    let fee_curve_max_basis_points = match fee_curve {
+        FeeCurve::Fixed => fee_basis_points,
+        FeeCurve::Linear => {
+            let max = max_fee_basis_points.unwrap_or(fee_basis_points);
+            require!(
+                max >= fee_basis_points && max <= config.max_fee_basis_points.min(MAX_FEE_BPS),
+                ErrorCode::InvalidFee
+            );
+            max
+        }
+    };
+    Ok((fee_curve, fee_curve_max_basis_points))
+}
+
+/// The early-exit haircut is a fee like any other, bounded by the program-wide fee cap
+fn validate_early_exit_fee(early_exit_fee_basis_points: u16, config: &ProgramConfig) -> Result<()> {
+    require!(
+        early_exit_fee_basis_points <= config.max_fee_basis_points,
+        ErrorCode::InvalidFee
+    );
+    Ok(())
+}
+
+/// Haircut for an LP exiting an open market, scaling linearly from the full
+/// `fee_basis_points` at creation down to zero at resolution. The fee is left in
+/// `liquidity_locked` so it accrues to the remaining LPs; settled markets exit at full value.
+fn calculate_early_exit_fee(amount: u64, market: &Market, now: i64, fee_basis_points: u16) -> u64 {
+    if market.is_resolved || now >= market.resolution_time {
+        return 0;
+    }
+    let total_window = (market.resolution_time - market.creation_timestamp).max(1) as u128;
+    let remaining = (market.resolution_time - now).clamp(0, total_window as i64) as u128;
+    (amount as u128 * fee_basis_points as u128 * remaining / (10_000 * total_window)) as u64
+}
+
+/// Freeze LP liquidity and supply at settlement so every LP's exit is priced against the
+/// same snapshot, whatever order they withdraw in
+fn snapshot_lp_entitlements(market: &mut Market) {
+    market.lp_settlement_liquidity = market.liquidity_locked;
+    market.lp_settlement_supply = market.total_lp_tokens;
+    market.lp_liquidity_withdrawn = 0;
+}
+
+/// Price and book an LP exit, returning the payout and the early-exit fee withheld. An open
+/// market pays the pro-rata share less the vault's early-exit fee, which stays in the pool
+/// for the remaining LPs; a settled market pays the exact snapshot share.
+fn withdraw_lp_liquidity(
+    vault: &mut Vault,
+    market: &mut Market,
+    lp_amount: u64,
+    now: i64,
+) -> Result<(u64, u64)> {
+    let (amount, exit_fee) = if market.is_resolved {
+        (settle_lp_withdrawal(vault, market, lp_amount)?, 0)
+    } else {
+        let gross = calculate_withdrawal_amount(
+            lp_amount,
+            market.liquidity_locked,
+            market.total_lp_tokens,
+        )?;
+        let exit_fee =
+            calculate_early_exit_fee(gross, market, now, vault.early_exit_fee_basis_points);
+        let amount = gross.checked_sub(exit_fee).ok_or(ErrorCode::MathOverflow)?;
+        release_liquidity(vault, market, amount)?;
+        (amount, exit_fee)
+    };
+    market.total_lp_tokens = market
+        .total_lp_tokens
+        .checked_sub(lp_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    Ok((amount, exit_fee))
+}
+
+/// Price an LP exit from a settled market at its exact snapshot share and book it. Shares
+/// round down, so the withdrawals together can never exceed the snapshot liquidity.
+fn settle_lp_withdrawal(vault: &mut Vault, market: &mut Market, lp_tokens: u64) -> Result<u64> {
+    require!(market.is_resolved, ErrorCode::MarketNotResolved);
+    require!(lp_tokens > 0, ErrorCode::ZeroAmount);
+    require!(
+        lp_tokens <= market.lp_settlement_supply,
+        ErrorCode::LpEntitlementExceeded
+    );
+
+    let share = checked_mul_div(
+        lp_tokens as u128,
+        market.lp_settlement_liquidity as u128,
+        market.lp_settlement_supply as u128,
+    )?;
+    let withdrawn = market
+        .lp_liquidity_withdrawn
+        .checked_add(share)
+        .ok_or(ErrorCode::LpEntitlementExceeded)?;
+    require!(
+        withdrawn <= market.lp_settlement_liquidity,
+        ErrorCode::LpEntitlementExceeded
+    );
+    market.lp_liquidity_withdrawn = withdrawn;
+    release_liquidity(vault, market, share)?;
+
+    Ok(share)
+}
+
+/// Every `liquidity_locked` decrement goes through here: liquidity leaving a market must
+/// have been tracked on both the market and the vault, so drift fails instead of wrapping
+fn release_liquidity(vault: &mut Vault, market: &mut Market, amount: u64) -> Result<()> {
+    market.liquidity_locked = market
+        .liquidity_locked
+        .checked_sub(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    vault.total_liquidity_locked = vault
+        .total_liquidity_locked
+        .checked_sub(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    Ok(())
+}
+
+/// Insured share of a losing stake that the bettor can reclaim
+fn calculate_insured_refund(stake: u64, insured_basis_points: u16) -> Result<u64> {
+    checked_mul_div(stake as u128, insured_basis_points as u128, 10_000)
+}
+
+/// Winner payout in an insured market: winners share the whole pool plus the insurance
+/// deposit, minus the insured refunds owed to the losing side
+fn calculate_insured_winner_payout(
+    stake: u64,
+    pools: &[u64],
+    winning_index: usize,
+    insurance_pool: u64,
+    insured_basis_points: u16,
+    burned: u64,
+) -> Result<u64> {
+    let total_pool: u128 = pools.iter().map(|&pool| pool as u128).sum();
+    let losing_pool = total_pool - pools[winning_index] as u128;
+    let insured_refunds = losing_pool * insured_basis_points as u128 / 10_000;
+    let distributable = (total_pool + insurance_pool as u128)
+        .checked_sub(insured_refunds)
+        .and_then(|pool| pool.checked_sub(burned as u128))
+        .ok_or(ErrorCode::MathOverflow)?;
+    checked_mul_div(stake as u128, distributable, pools[winning_index] as u128)
+}
+
+/// Scale a payout by `available / total_claimable` when the vault is underfunded
+fn apply_safe_mode_haircut(payout: u64, available: u64, total_claimable: u64) -> Result<u64> {
+    if total_claimable == 0 {
+        return Ok(0);
+    }
+    checked_mul_div(payout as u128, available as u128, total_claimable as u128)
+}
+
+/// Transfer `amount` into the vault and return the vault balance delta, which is what
+/// callers must credit
+fn receive_into_vault<'info>(
+    from: &Account<'info, TokenAccount>,
+    vault_token_account: &Account<'info, TokenAccount>,
+    authority: AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<u64> {
+    let before = token_balance(vault_token_account)?;
+    let cpi_accounts = Transfer {
+        from: from.to_account_info(),
+        to: vault_token_account.to_account_info(),
+        authority,
+    };
+    token::transfer(
+        CpiContext::new(token_program.to_account_info(), cpi_accounts),
+        amount,
+    )?;
+    let received = token_balance(vault_token_account)?
+        .checked_sub(before)
+        .ok_or(ErrorCode::MathOverflow)?;
+    require!(received > 0, ErrorCode::ZeroAmount);
+    Ok(received)
+}
+
+/// Current balance of a token account, read from its data rather than the copy
+/// deserialized when the instruction started
+fn token_balance(account: &Account<TokenAccount>) -> Result<u64> {
+    let info = account.to_account_info();
+    let data = info.try_borrow_data()?;
+    Ok(TokenAccount::try_deserialize(&mut &data[..])?.amount)
+}
+
+/// Transfer tokens out of the vault token account, signing with the vault seeds
+fn transfer_from_vault<'info>(
+    vault: &Account<'info, Vault>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let vault_key = vault.key();
+    let seeds = &[b"vault".as_ref(), vault_key.as_ref(), &[vault.nonce]];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: from.to_account_info(),
+        to: to.to_account_info(),
+        authority: vault.to_account_info(),
+    };
+    let cpi_ctx =
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds);
+    token::transfer(cpi_ctx, amount)
+}
+
+/// Pay a market's accrued keeper tips to its resolver. The pool is zeroed before the
+/// transfer and markets resolve only once, so tips can never be paid twice.
+fn pay_keeper_pool<'info>(
+    market: &mut Account<'info, Market>,
+    vault: &Account<'info, Vault>,
+    vault_token_account: &Account<'info, TokenAccount>,
+    resolver_token_account: &Option<Account<'info, TokenAccount>>,
+    resolver: &Pubkey,
+    token_program: &Program<'info, Token>,
+    now: i64,
+) -> Result<u64> {
+    let keeper_tip = market.keeper_pool;
+    if keeper_tip == 0 {
+        return Ok(0);
+    }
+    let resolver_token_account = resolver_token_account
+        .as_ref()
+        .ok_or(ErrorCode::InvalidKeeperAccount)?;
+    require!(
+        resolver_token_account.owner == *resolver
+            && resolver_token_account.mint == market.settlement_mint,
+        ErrorCode::InvalidKeeperAccount
+    );
+
+    market.keeper_pool = 0;
+    transfer_from_vault(
+        vault,
+        vault_token_account,
+        resolver_token_account,
+        token_program,
+        keeper_tip,
+    )?;
+
+    emit!(KeeperTipPaid {
+        market: market.key(),
+        keeper: *resolver,
+        amount: keeper_tip,
+        timestamp: now,
+    });
+
+    Ok(keeper_tip)
+}
+
+/// Mint LP tokens to a provider, signing as the vault PDA that holds the mint authority
+fn mint_lp_tokens<'info>(
+    vault: &Account<'info, Vault>,
+    lp_mint: &Account<'info, Mint>,
+    to: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let vault_key = vault.key();
+    let seeds = &[b"vault".as_ref(), vault_key.as_ref(), &[vault.nonce]];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_accounts = MintTo {
+        mint: lp_mint.to_account_info(),
+        to: to.to_account_info(),
+        authority: vault.to_account_info(),
+    };
+    let cpi_ctx =
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds);
+    token::mint_to(cpi_ctx, amount)
+}
+
+/// Burn tokens held by the vault token account, signing with the vault seeds
+fn burn_from_vault<'info>(
+    vault: &Account<'info, Vault>,
+    mint: &Account<'info, Mint>,
+    from: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let vault_key = vault.key();
+    let seeds = &[b"vault".as_ref(), vault_key.as_ref(), &[vault.nonce]];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_accounts = Burn {
+        mint: mint.to_account_info(),
+        from: from.to_account_info(),
+        authority: vault.to_account_info(),
+    };
+    let cpi_ctx =
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds);
+    token::burn(cpi_ctx, amount)
+}
+
+/// Yes and No LMSR prices in basis points, each clamped to the valid stored range
+fn calculate_lmsr_quotes(yes: u64, no: u64, liquidity: u64) -> (u64, u64) {
+    if liquidity == 0 {
+        let yes_probability = calculate_initial_probability(&[yes, no], Outcome::Yes.index());
+        return (
+            clamp_probability(yes_probability),
+            clamp_probability(10000 - yes_probability),
+        );
+    }
+
+    (
+        clamp_probability(calculate_lmsr_probability(yes, no, liquidity)),
+        clamp_probability(calculate_lmsr_probability(no, yes, liquidity)),
+    )
+}
+
+/// Convert an LMSR probability to basis points, rounding to nearest (half away from zero)
+/// so displayed odds carry no downward truncation bias. The result stays within `0..=10000`.
+fn probability_to_bps(probability: f64) -> u64 {
+    (probability * 10000.0).round().clamp(0.0, 10000.0) as u64
+}
+
+/// Clamp a probability into the valid stored range, guarding against f64 cast artifacts
+fn clamp_probability(probability: u64) -> u64 {
+    probability.clamp(MIN_PROBABILITY_BPS, MAX_PROBABILITY_BPS)
+}
+
+fn calculate_lp_tokens(amount: u64, locked: u64, supply: u64) -> Result<u64> {
+    if supply == 0 {
+        Ok(amount) // Initial liquidity
+    } else {
+        checked_mul_div(amount as u128, supply as u128, locked as u128)
+    }
+}
+
+/// Inverse of `calculate_lp_tokens`: liquidity owed for burning `lp_tokens`. Both round
+/// down, so a deposit followed by a full withdrawal never returns more than was put in.
+fn calculate_withdrawal_amount(lp_tokens: u64, locked: u64, supply: u64) -> Result<u64> {
+    require!(lp_tokens <= supply, ErrorCode::LpEntitlementExceeded);
+    checked_mul_div(lp_tokens as u128, locked as u128, supply as u128)
+}
+
+/// Market-scoped nullifier so the same secret yields unrelated nullifiers across markets
+fn derive_nullifier(secret: &[u8; 32], market_id: &[u8; 32]) -> [u8; 32] {
+    hashv(&[secret, market_id]).to_bytes()
+}
+
+/// Deterministic per-bet idempotency key: keccak(market || bettor || nullifier)
+fn derive_bet_id(market: &Pubkey, bettor: &Pubkey, nullifier: &[u8; 32]) -> [u8; 32] {
+    hashv(&[market.as_ref(), bettor.as_ref(), nullifier]).to_bytes()
+}
+
+/// Log remaining compute units at a labelled point; compiled out unless the `debug` feature is on
+#[cfg(feature = "debug")]
+fn log_compute_units(label: &str) {
+    msg!("CU {}", label);
+    solana_program::log::sol_log_compute_units();
+}
+
+#[cfg(not(feature = "debug"))]
+#[inline(always)]
+fn log_compute_units(_label: &str) {}
+
+/// Read a Pyth price feed, rejecting prices older than `MAX_PRICE_AGE_SECONDS`
+fn read_reference_price(price_account: &AccountInfo, now: i64) -> Result<(i64, i32)> {
+    let feed = load_price_feed_from_account_info(price_account)
+        .map_err(|_| error!(ErrorCode::InvalidPriceReference))?;
+    let price = feed
+        .get_price_no_older_than(now, MAX_PRICE_AGE_SECONDS)
+        .ok_or(ErrorCode::StalePriceReference)?;
+    Ok((price.price, price.expo))
+}
+
+/// Reject the instruction unless the vault has the given feature bit enabled
+fn require_feature(vault: &Vault, feature: u64) -> Result<()> {
+    require!(vault.feature_flags & feature != 0, ErrorCode::FeatureDisabled);
+    Ok(())
+}
+
+/// Creation checks shared by `create_market` and its `validate_market_params` dry run.
+/// Returns the bare error code so the dry run can report it instead of failing.
+fn check_market_params(
+    vault: &Account<Vault>,
+    creator: &Pubkey,
+    allowlist_entry: &Option<Account<AllowedCreator>>,
+    resolution_time: i64,
+    oracle: &Pubkey,
+    now: i64,
+) -> std::result::Result<(), ErrorCode> {
+    if vault.new_markets_paused {
+        return Err(ErrorCode::NewMarketsPaused);
+    }
+    if vault.is_paused {
+        return Err(ErrorCode::VaultPaused);
+    }
+    if vault.wind_down {
+        return Err(ErrorCode::VaultWindingDown);
+    }
+    if resolution_time <= now {
+        return Err(ErrorCode::InvalidResolutionTime);
+    }
+    if resolution_time - now < vault.min_resolution_offset_seconds {
+        return Err(ErrorCode::ResolutionTooSoon);
+    }
+    if !creator_allowed(vault, creator, allowlist_entry) {
+        return Err(ErrorCode::CreatorNotAllowed);
+    }
+    if !vault.allow_self_oracle && creator == oracle {
+        return Err(ErrorCode::SelfOracleNotAllowed);
+    }
+    Ok(())
+}
+
+/// Count a new market against its creator's open-market cap
+fn open_creator_market(
+    stats: &mut Account<CreatorStats>,
+    vault: &Account<Vault>,
+    creator: Pubkey,
+) -> Result<()> {
+    stats.vault = vault.key();
+    stats.creator = creator;
+    require!(
+        vault.max_open_markets_per_creator == 0
+            || stats.open_markets < vault.max_open_markets_per_creator,
+        ErrorCode::CreatorMarketLimit
+    );
+    stats.open_markets = stats.open_markets.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    Ok(())
+}
+
+/// Free the creator's open-market slot once the market settles. Markets created before
+/// creator stats existed hold no slot and need no stats account.
+fn release_creator_slot(
+    market: &mut Market,
+    stats: Option<&mut Account<CreatorStats>>,
+) -> Result<()> {
+    if !market.holds_creator_slot {
+        return Ok(());
+    }
+    let stats = stats.ok_or(ErrorCode::CreatorStatsRequired)?;
+    stats.open_markets = stats.open_markets.checked_sub(1).ok_or(ErrorCode::MathOverflow)?;
+    market.holds_creator_slot = false;
+    Ok(())
+}
+
+/// Longest the vault's configured windows can hold funds past `resolution_time`: every
+/// allowed resolution extension, then the community vote window, the authority override
+/// window, the challenge window, the cancellation grace and the claim window. Saturates
+/// rather than overflowing, which the horizon check then rejects.
+fn configured_lockup_seconds(vault: &Vault) -> u64 {
+    let extensions = vault.max_total_extension_seconds.min(
+        vault
+            .resolution_extension_seconds
+            .saturating_mul(vault.max_resolution_extensions as u64),
+    );
+    extensions
+        .saturating_add(vault.community_vote_window_seconds)
+        .saturating_add(vault.authority_override_window_seconds)
+        .saturating_add(vault.challenge_window_seconds)
+        .saturating_add(vault.cancellation_grace_seconds)
+        .saturating_add(vault.claim_window_seconds)
+}
+
+/// Reject a vault configuration whose windows could lock funds past its lockup bound
+fn require_lockup_horizon(vault: &Vault) -> Result<()> {
+    require!(
+        configured_lockup_seconds(vault) <= vault.max_total_lockup_seconds,
+        ErrorCode::LockupHorizonExceeded
+    );
+    Ok(())
+}
+
+/// Reject any instruction that moves new funds into a vault that is paused or winding down
+fn require_inflows_open(vault: &Vault) -> Result<()> {
+    require!(!vault.is_paused, ErrorCode::VaultPaused);
+    require!(!vault.wind_down, ErrorCode::VaultWindingDown);
+    Ok(())
+}
+
+/// Shared body of `pause_vault` / `unpause_vault`
+fn set_vault_paused(vault: &mut Account<Vault>, authority: &Signer, paused: bool) -> Result<()> {
+    require!(authority.key() == vault.authority, ErrorCode::Unauthorized);
+    require!(vault.is_paused != paused, ErrorCode::PauseStateUnchanged);
+
+    vault.is_paused = paused;
+
+    let timestamp = current_timestamp()?;
+    if paused {
+        emit!(VaultPaused {
+            vault: vault.key(),
+            timestamp,
+        });
+    } else {
+        emit!(VaultUnpaused {
+            vault: vault.key(),
+            timestamp,
+        });
+    }
+
+    Ok(())
+}
+
+/// Shared body of `pause_new_markets` / `resume_new_markets`
+fn set_new_markets_paused(
+    vault: &mut Account<Vault>,
+    authority: &Signer,
+    paused: bool,
+) -> Result<()> {
+    require!(authority.key() == vault.authority, ErrorCode::Unauthorized);
+
+    vault.new_markets_paused = paused;
+
+    emit!(NewMarketsPauseUpdated {
+        vault: vault.key(),
+        paused,
+        timestamp: current_timestamp()?,
+    });
+
+    Ok(())
+}
+
+/// Under `CreatorPolicy::Allowlisted` the creator must present their allowlist entry
+fn require_creator_allowed(
+    vault: &Account<Vault>,
+    creator: &Pubkey,
+    allowlist_entry: &Option<Account<AllowedCreator>>,
+) -> Result<()> {
+    require!(
+        creator_allowed(vault, creator, allowlist_entry),
+        ErrorCode::CreatorNotAllowed
+    );
+    Ok(())
+}
+
+fn creator_allowed(
+    vault: &Account<Vault>,
+    creator: &Pubkey,
+    allowlist_entry: &Option<Account<AllowedCreator>>,
+) -> bool {
+    if vault.creator_policy == CreatorPolicy::Permissionless {
+        return true;
+    }
+    match allowlist_entry {
+        Some(entry) => entry.vault == vault.key() && entry.creator == *creator,
+        None => false,
+    }
+}
+
+/// Whether `mint` is the vault's own settlement mint or one of its accepted extras
+fn is_settlement_mint(vault: &Vault, mint: &Pubkey) -> bool {
+    *mint == vault.settlement_mint || extra_settlement_mint_slot(vault, mint).is_some()
+}
+
+/// Decimals of an accepted settlement mint, as recorded when the vault accepted it
+fn settlement_mint_decimals(vault: &Vault, mint: &Pubkey) -> Result<u8> {
+    if *mint == vault.settlement_mint {
+        return Ok(vault.mint_decimals);
+    }
+    let slot =
+        extra_settlement_mint_slot(vault, mint).ok_or(ErrorCode::UnsupportedSettlementMint)?;
+    Ok(vault.extra_settlement_mint_decimals[slot])
+}
+
+/// Slot of `mint` in the vault's extra settlement mints
+fn extra_settlement_mint_slot(vault: &Vault, mint: &Pubkey) -> Option<usize> {
+    vault
+        .extra_settlement_mints
+        .iter()
+        .position(|extra| extra == mint)
+}
+
+/// Funds flowing in must land in a token account the vault owns, not any account that
+/// merely holds the right mint
+fn require_vault_token_account(account: &Account<TokenAccount>, vault: &Pubkey) -> Result<()> {
+    require!(account.owner == *vault, ErrorCode::InvalidVaultTokenAccount);
+    Ok(())
+}
+
+/// Bind a market to its LP mint on the first deposit, then hold every later deposit to it.
+/// Only the vault PDA may mint, so the mint's supply tracks `total_lp_tokens` exactly.
+fn require_lp_mint(market: &mut Market, lp_mint: &Account<Mint>, vault: &Pubkey) -> Result<()> {
+    if market.lp_mint == Pubkey::default() {
+        require!(
+            lp_mint.mint_authority == COption::Some(*vault)
+                && lp_mint.supply == 0
+                && lp_mint.decimals == market.mint_decimals,
+            ErrorCode::InvalidLpMint
+        );
+        market.lp_mint = lp_mint.key();
+    }
+    require!(lp_mint.key() == market.lp_mint, ErrorCode::InvalidLpMint);
+    Ok(())
+}
+
+fn require_lp_token_account(
+    market: &Market,
+    account: &Account<TokenAccount>,
+    provider: &Pubkey,
+) -> Result<()> {
+    require!(
+        account.mint == market.lp_mint && account.owner == *provider,
+        ErrorCode::InvalidLpTokenAccount
+    );
+    Ok(())
+}
+
+/// Every token account moving a market's funds must hold the market's settlement mint
+fn require_settlement_mint(
+    market: &Market,
+    token_accounts: &[&Account<'_, TokenAccount>],
+) -> Result<()> {
+    require!(
+        token_accounts
+            .iter()
+            .all(|account| account.mint == market.settlement_mint),
+        ErrorCode::SettlementMintMismatch
+    );
+    Ok(())
+}
+
+/// Emit a single bounded, stable-prefixed log line for a failed verification and pass the error through
+fn log_verification_failure(component: &str, market: &Pubkey, error: Error) -> Error {
+    msg!("VERIFICATION_FAILED component={} market={}", component, market);
+    error
+}
+
+/// Allowlist leaf for a bettor
+fn merkle_leaf(bettor: &Pubkey) -> [u8; 32] {
+    hashv(&[bettor.as_ref()]).to_bytes()
+}
+
+/// Commutative keccak hash of two sibling nodes (pairs are sorted before hashing)
+fn hash_merkle_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    if a <= b {
+        hashv(&[a, b]).to_bytes()
+    } else {
+        hashv(&[b, a]).to_bytes()
+    }
+}
+
+/// Reject proofs deeper than `max_depth` 32-byte siblings before hashing anything, so
+/// verification cost stays bounded whatever the caller submits
+fn require_proof_depth(proof: &[u8], max_depth: u8) -> Result<()> {
+    let depth = (proof.len() + 31) / 32;
+    require!(depth <= max_depth as usize, ErrorCode::ProofTooDeep);
+    Ok(())
+}
+
+/// Verify that `leaf` is in the allowlist tree rooted at `root`. `proof` is the
+/// concatenation of 32-byte siblings from the leaf upwards; pairs are hashed sorted, so no
+/// direction flags are needed.
+fn verify_merkle_proof(proof: &[u8], root: [u8; 32], leaf: Pubkey) -> Result<()> {
+    require!(proof.len() % 32 == 0, ErrorCode::InvalidMerkleProof);
+
+    let mut siblings: Vec<[u8; 32]> = Vec::with_capacity(proof.len() / 32);
+    for sibling in proof.chunks_exact(32) {
+        siblings.push(
+            sibling
+                .try_into()
+                .map_err(|_| ErrorCode::InvalidMerkleProof)?,
+        );
+    }
+    // A single-leaf proof is the multiproof whose every step takes the next sibling
+    let proof_flags = vec![false; siblings.len()];
+    verify_merkle_multiproof(&[merkle_leaf(&leaf)], &siblings, &proof_flags, root)
+}
+
+/// Verify several leaves against one root with shared sibling hashes, using the standard
+/// multiproof layout: `proof_flags[i]` selects whether the second input of step `i` comes
+/// from the leaf/hash queue (true) or the next `proof` element (false). Leaves must be
+/// supplied in the order they appear in the tree.
+fn verify_merkle_multiproof(
+    leaves: &[[u8; 32]],
+    proof: &[[u8; 32]],
+    proof_flags: &[bool],
+    root: [u8; 32],
+) -> Result<()> {
+    let total_hashes = proof_flags.len();
+    require!(
+        leaves.len() + proof.len() == total_hashes + 1,
+        ErrorCode::InvalidMerkleProof
+    );
+
+    let mut hashes: Vec<[u8; 32]> = Vec::with_capacity(total_hashes);
+    let (mut leaf_pos, mut hash_pos, mut proof_pos) = (0usize, 0usize, 0usize);
+
+    for &use_queue in proof_flags {
+        let a = next_multiproof_node(leaves, &hashes, &mut leaf_pos, &mut hash_pos)?;
+        let b = if use_queue {
+            next_multiproof_node(leaves, &hashes, &mut leaf_pos, &mut hash_pos)?
+        } else {
+            let sibling = *proof.get(proof_pos).ok_or(ErrorCode::InvalidMerkleProof)?;
+            proof_pos += 1;
+            sibling
+        };
+        hashes.push(hash_merkle_pair(&a, &b));
+    }
+
+    let computed_root = if total_hashes > 0 {
+        require!(proof_pos == proof.len(), ErrorCode::InvalidMerkleProof);
+        hashes[total_hashes - 1]
+    } else if !leaves.is_empty() {
+        leaves[0]
+    } else {
+        proof[0]
+    };
+    require!(computed_root == root, ErrorCode::InvalidMerkleProof);
+
+    Ok(())
+}
+
+/// Pop the next node from the multiproof queue: remaining leaves first, then computed hashes
+fn next_multiproof_node(
+    leaves: &[[u8; 32]],
+    hashes: &[[u8; 32]],
+    leaf_pos: &mut usize,
+    hash_pos: &mut usize,
+) -> Result<[u8; 32]> {
+    let node = if *leaf_pos < leaves.len() {
+        *leaf_pos += 1;
+        leaves[*leaf_pos - 1]
+    } else {
+        *hash_pos += 1;
+        *hashes.get(*hash_pos - 1).ok_or(ErrorCode::InvalidMerkleProof)?
+    };
+    Ok(node)
+}
+
+fn verify_oracle_signature(
+    signature: &[u8],
+    market: &Market,
+    outcome_index: u8,
+    source_ref: &[u8; 32],
+    instructions: &AccountInfo,
+) -> Result<()> {
+    verify_oracle_message(
+        signature,
+        &oracle_outcome_message(&market.id, outcome_index, source_ref),
+        &market.oracle,
+        market.oracle_signature_scheme,
+        instructions,
+    )
+}
+
+/// Ed25519 oracles sign resolutions themselves. A secp256k1 oracle has no Solana key, so any
+/// relayer may submit its resolution and the recovered signature alone authorizes it.
+fn is_oracle_submitter(market: &Market, submitter: &Pubkey) -> bool {
+    match market.oracle_signature_scheme {
+        OracleSignatureScheme::Ed25519 => *submitter == market.oracle,
+        OracleSignatureScheme::Secp256k1 => true,
+    }
+}
+
+/// Message an oracle signs to attest a winning outcome and the data source it relied on
+fn oracle_outcome_message(
+    market_id: &[u8; 32],
+    outcome_index: u8,
+    source_ref: &[u8; 32],
+) -> Vec<u8> {
+    let mut message = market_id.to_vec();
+    message.push(outcome_index);
+    message.extend_from_slice(source_ref);
+    message
+}
+
+/// Message an oracle signs to attest a Yes probability (basis points)
+fn oracle_probability_message(market_id: &[u8; 32], yes_probability: u64) -> Vec<u8> {
+    let mut message = market_id.to_vec();
+    message.extend_from_slice(&yes_probability.to_le_bytes());
+    message
+}
+
+fn verify_oracle_message(
+    signature: &[u8],
+    message: &[u8],
+    oracle: &Pubkey,
+    scheme: OracleSignatureScheme,
+    instructions: &AccountInfo,
+) -> Result<()> {
+    match scheme {
+        OracleSignatureScheme::Ed25519 => {
+            verify_ed25519_message(instructions, signature, message, oracle)
+        }
+        OracleSignatureScheme::Secp256k1 => verify_secp256k1_message(signature, message, oracle),
+    }
+}
+
+/// Programs cannot verify Ed25519 signatures themselves; the native Ed25519 program does it
+/// in an earlier instruction of the same transaction, failing the transaction if any
+/// signature is invalid. Find one that verified exactly `(oracle, message, signature)`.
+fn verify_ed25519_message(
+    instructions: &AccountInfo,
+    signature: &[u8],
+    message: &[u8],
+    oracle: &Pubkey,
+) -> Result<()> {
+    require!(signature.len() == 64, ErrorCode::InvalidOracleSignature);
+    let current_index = load_current_index_checked(instructions)?;
+    for index in 0..current_index {
+        let instruction = load_instruction_at_checked(index as usize, instructions)?;
+        if instruction.program_id == ed25519_program::ID
+            && ed25519_instruction_verifies(&instruction.data, signature, message, oracle)
+        {
+            return Ok(());
+        }
+    }
+    err!(ErrorCode::InvalidOracleSignature)
+}
+
+/// Whether Ed25519 program instruction data verifies `(oracle, message, signature)`. The
+/// data is a signature count and padding byte followed by one 14-byte offsets record per
+/// signature; only inputs stored in the instruction's own data (index `u16::MAX`) count.
+fn ed25519_instruction_verifies(
+    data: &[u8],
+    signature: &[u8],
+    message: &[u8],
+    oracle: &Pubkey,
+) -> bool {
+    let Some(&count) = data.first() else {
+        return false;
+    };
+    (0..count as usize).any(|i| {
+        let start = 2 + i * 14;
+        let Some(offsets) = data.get(start..start + 14) else {
+            return false;
+        };
+        let field = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]) as usize;
+        let inline = u16::MAX as usize;
+        if field(2) != inline || field(6) != inline || field(12) != inline {
+            return false;
+        }
+        let (signature_at, oracle_at, message_at) = (field(0), field(4), field(8));
+        data.get(signature_at..signature_at + 64) == Some(signature)
+            && data.get(oracle_at..oracle_at + 32) == Some(oracle.as_ref())
+            && data.get(message_at..message_at + field(10)) == Some(message)
+    })
+}
+
+/// Recover the signer of a 65-byte `r || s || v` signature over `keccak(message)` and bind
+/// it to the oracle, which for secp256k1 markets holds keccak of the uncompressed public key
+fn verify_secp256k1_message(signature: &[u8], message: &[u8], oracle: &Pubkey) -> Result<()> {
+    require!(signature.len() == 65, ErrorCode::InvalidOracleSignature);
+    // Accept both raw (0/1) and EVM-style (27/28) recovery ids
+    let recovery_id = match signature[64] {
+        id @ (0 | 1) => id,
+        id @ (27 | 28) => id - 27,
+        _ => return err!(ErrorCode::InvalidOracleSignature),
+    };
+    let digest = hashv(&[message]).to_bytes();
+    let recovered = secp256k1_recover(&digest, recovery_id, &signature[..64])
+        .map_err(|_| ErrorCode::InvalidOracleSignature)?;
+    require!(
+        hashv(&[&recovered.to_bytes()]).to_bytes() == oracle.to_bytes(),
+        ErrorCode::InvalidOracleSignature
+    );
+    Ok(())
+}
+
+fn verify_zk_proof(proof: &[u8], nullifier: &[u8; 32], claimant: &Pubkey) -> Result<()> {
+    // Zero-knowledge proof verification
+    // Would integrate with a ZK library like Groth16 or PLONK
+    Ok(())
+}
+
+// ===== Account Structures =====
+
+#[account]
+pub struct ProgramConfig {
+    pub authority: Pubkey,
+    pub max_fee_basis_points: u16,
+}
+
+#[account]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub fee_basis_points: u16,
+    pub min_bet_amount: u64,
+    pub total_volume: u64,
+    pub total_fees_collected: u64,
+    pub nonce: u8,
+    pub is_paused: bool,
+    pub creation_timestamp: i64,
+    pub donation_basis_points: u16,
+    pub donation_recipient: Pubkey,
+    pub pending_donations: u64,
+    pub total_donations_distributed: u64,
+    pub early_exit_fee_basis_points: u16,
+    pub total_liquidity_locked: u64,
+    pub market_count: u64,
+    pub resolution_extension_seconds: u64,
+    pub max_resolution_extensions: u8,
+    /// Hard ceiling on the cumulative extension of any market past its original resolution time
+    pub max_total_extension_seconds: u64,
+    pub allow_self_oracle: bool,
+    pub settlement_mint: Pubkey,
+    pub min_resolution_offset_seconds: i64,
+    pub program_version: u16,
+    pub feature_flags: u64,
+    /// Additional accepted settlement mints; `Pubkey::default()` marks an empty slot
+    pub extra_settlement_mints: [Pubkey; MAX_EXTRA_SETTLEMENT_MINTS],
+    /// Fees collected per extra mint, indexed like `extra_settlement_mints`
+    pub extra_settlement_fees_collected: [u64; MAX_EXTRA_SETTLEMENT_MINTS],
+    pub creator_policy: CreatorPolicy,
+    /// Share of the losing pool burned at resolution, snapshotted into each new market
+    pub burn_basis_points: u16,
+    pub max_merkle_depth: u8,
+    pub bet_count: u64,
+    /// Next bet sequence expected by an in-progress `reconcile_volume` pass
+    pub volume_reconcile_cursor: u64,
+    /// Volume summed so far by the in-progress `reconcile_volume` pass
+    pub volume_reconcile_sum: u64,
+    /// Liquidity every `create_market` must seed atomically; zero makes seeding optional
+    pub min_initial_liquidity: u64,
+    /// Default for new markets: nullifier-bound bets and ZK-proven claims
+    pub privacy_enabled: bool,
+    /// Share of each net stake set aside for whoever resolves the market
+    pub keeper_tip_basis_points: u16,
+    /// Per-market LP liquidity cap for markets created from now on; zero means unlimited
+    pub max_liquidity: u64,
+    /// Fixed stake for one survey vote; zero disables survey voting
+    pub survey_stake: u64,
+    /// Claim-time fee on winnings, computed per `winnings_fee_basis`
+    pub winnings_fee_basis_points: u16,
+    pub winnings_fee_basis: WinningsFeeBasis,
+    /// Blocks market creation only; existing markets keep trading and settling
+    pub new_markets_paused: bool,
+    /// Decimals of `settlement_mint` and of each extra settlement mint, by slot
+    pub mint_decimals: u8,
+    pub extra_settlement_mint_decimals: [u8; MAX_EXTRA_SETTLEMENT_MINTS],
+    /// Oracle signature scheme for markets created from now on
+    pub oracle_signature_scheme: OracleSignatureScheme,
+    /// Share of the bet fee rebated to bets that shrink a market's pool imbalance
+    pub balancing_rebate_basis_points: u16,
+    /// Irreversible exit-only mode; see `enter_wind_down`
+    pub wind_down: bool,
+    /// Vote quorum and window for community-resolved markets created from now on
+    pub community_quorum_basis_points: u16,
+    pub community_vote_window_seconds: u64,
+    /// Cap on each creator's unresolved markets; zero means unlimited
+    pub max_open_markets_per_creator: u32,
+    /// Time after an oracle resolution during which the authority may correct the outcome
+    /// and claims stay locked; zero disables overrides
+    pub authority_override_window_seconds: u64,
+    /// Whether bettors must prove membership in `merkle_root`; set when the vault is
+    /// initialized with a non-zero root
+    pub allowlist_enabled: bool,
+    /// Implied probability (basis points) of the side being bet above which its minimum bet
+    /// scales up; zero disables odds-scaled minimums
+    pub odds_scaled_min_threshold_bps: u16,
+    /// Cap on the odds-scaled minimum as a multiple of the market's `min_bet_amount`
+    pub odds_scaled_min_max_multiplier: u16,
+    /// Portion of `total_fees_collected` already paid out by `withdraw_fees`
+    pub total_fees_withdrawn: u64,
+    /// Bound on the sum of every configured window that can hold funds past
+    /// `resolution_time`; see `configured_lockup_seconds`
+    pub max_total_lockup_seconds: u64,
+    /// Time after `resolution_time` before an unresolved market may be cancelled
+    pub cancellation_grace_seconds: u64,
+    /// How long claims stay open once they unlock; zero means claims never expire
+    pub claim_window_seconds: u64,
+    /// How long an oracle resolution stays open to dispute before claims open
+    pub challenge_window_seconds: u64,
+    /// Bond `raise_dispute` posts in the market's settlement mint; zero disables disputes
+    pub dispute_bond_amount: u64,
+    /// How new markets price fees over their lifetime
+    pub fee_curve: FeeCurve,
+    /// Fee a linear curve reaches at `resolution_time`
+    pub fee_curve_max_basis_points: u16,
+}
+
+#[account]
+pub struct Market {
+    pub id: [u8; 32],
+    pub vault: Pubkey,
+    pub creator: Pubkey,
+    pub oracle: Pubkey,
+    pub resolution_time: i64,
+    pub commitment_hash: [u8; 32],
+    pub total_yes_amount: u64,
+    pub total_no_amount: u64,
+    pub is_resolved: bool,
+    pub winning_outcome: Option<Outcome>,
+    pub creation_timestamp: i64,
+    pub resolution_timestamp: i64,
+    pub implied_probability: u64,
+    pub liquidity_locked: u64,
+    pub fee_basis_points: u16,
+    pub min_bet_amount: u64,
+    pub total_claimable: u64,
+    pub total_claimed: u64,
+    pub safe_mode: bool,
+    pub safe_mode_available: u64,
+    pub is_probabilistic: bool,
+    pub resolved_yes_probability: u64,
+    pub opening_time: i64,
+    pub min_seed_per_outcome: u64,
+    pub is_tradable: bool,
+    pub source_ref: [u8; 32],
+    pub sequence: u64,
+    pub insured_basis_points: u16,
+    pub insurance_pool: u64,
+    pub price_reference_account: Option<Pubkey>,
+    pub extension_count: u8,
+    pub self_oracle: bool,
+    pub creator_seed_fee_exempt: bool,
+    pub creator_seed_used: bool,
+    pub settlement_mint: Pubkey,
+    pub burn_basis_points: u16,
+    pub burned_amount: u64,
+    pub privacy_enabled: bool,
+    pub keeper_tip_basis_points: u16,
+    pub keeper_pool: u64,
+    pub max_liquidity: u64,
+    pub survey_yes_votes: u64,
+    pub survey_no_votes: u64,
+    pub survey_abstain_votes: u64,
+    pub total_extension_seconds: u64,
+    /// LP tokens issued against this market's liquidity
+    pub total_lp_tokens: u64,
+    /// LP liquidity and LP token supply frozen at settlement; withdrawals are paid from these
+    pub lp_settlement_liquidity: u64,
+    pub lp_settlement_supply: u64,
+    pub lp_liquidity_withdrawn: u64,
+    /// Mint of this market's LP tokens, bound by the first deposit; default until then
+    pub lp_mint: Pubkey,
+    /// Decimals of `settlement_mint`, echoed in amount-carrying events
+    pub mint_decimals: u8,
+    /// Bumped by every resolution; claims are checked against it
+    pub settlement_generation: u64,
+    /// False for trusted-oracle markets resolved on signature alone
+    pub commit_reveal_enabled: bool,
+    pub oracle_signature_scheme: OracleSignatureScheme,
+    /// Resolved by a stake-weighted vote of its bettors instead of an oracle
+    pub community_resolved: bool,
+    /// The oracle may resolve before `resolution_time` once the event has concluded
+    pub allow_early_resolution: bool,
+    pub community_quorum_basis_points: u16,
+    pub community_vote_window_seconds: u64,
+    /// Stake-weighted resolution votes, indexed by outcome index
+    pub resolution_votes: [u64; MAX_OUTCOMES],
+    /// Settled without a winner; every stake is refunded
+    pub is_void: bool,
+    /// Voided by `cancel_market` after it went unresolved past its grace period
+    pub is_cancelled: bool,
+    /// Counted in the creator's `CreatorStats::open_markets` until settled
+    pub holds_creator_slot: bool,
+    /// Bets recorded in this market; each bet's `market_index` is its position
+    pub bet_count: u64,
+    /// Bets processed so far by the batched settlement pass (`commit_entitlements`); the
+    /// next batch must start at this `market_index`
+    pub settlement_cursor: u64,
+    /// Set once, by the batch that processes the market's last bet
+    pub settlement_complete: bool,
+    /// Left siblings along the append path of the incremental entitlement tree
+    pub entitlement_frontier: [[u8; 32]; ENTITLEMENT_TREE_DEPTH],
+    /// Merkle root over every bet's `(bettor, bet, entitlement)` leaf; all zero until committed
+    pub entitlement_root: [u8; 32],
+    /// Claims open at this time; until then the authority may override the oracle outcome
+    pub claims_unlock_time: i64,
+    /// Number of mutually exclusive outcomes; 2 for a Yes/No market
+    pub outcome_count: u8,
+    /// Stake on each outcome, indexed by outcome index; only the first `outcome_count` are used
+    pub outcome_totals: [u64; MAX_OUTCOMES],
+    /// Index of the winning outcome once resolved; `None` while open or when voided
+    pub winning_index: Option<u8>,
+    /// Claims are rejected from this time on; zero means they never expire
+    pub claim_deadline: i64,
+    /// Unclaimed winnings moved to the treasury by `sweep_unclaimed` after the deadline
+    pub unclaimed_swept: u64,
+    /// LMSR share quantities `q` sold so far, indexed by `Outcome::index`; Yes/No markets only
+    pub outcome_shares: [u64; 2],
+    /// End of the challenge window of an oracle resolution; claims wait for it
+    pub resolution_finalizes_at: i64,
+    /// A dispute is pending; claims are frozen until `resolve_dispute` rules on it
+    pub is_disputed: bool,
+    pub disputer: Pubkey,
+    /// Bond held in the vault for the pending dispute
+    pub dispute_bond: u64,
+    /// Fee curve and its maximum, fixed at creation; `fee_basis_points` is the base fee
+    pub fee_curve: FeeCurve,
+    pub fee_curve_max_basis_points: u16,
+    /// `OutcomeSchema` labeling the outcomes; the default key when they are unlabeled
+    pub outcome_schema: Pubkey,
+}
+
+#[account]
+pub struct MarketTemplate {
+    pub vault: Pubkey,
+    pub oracle: Pubkey,
+    pub default_duration: u64,
+    pub fee_basis_points: u16,
+    pub min_bet_amount: u64,
+}
+
+#[account]
+pub struct BetAccount {
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub amount: u64,
+    /// Yes/No side of the bet; `None` in a multi-outcome market, where only `outcome_index`
+    /// identifies the outcome
+    pub outcome: Option<Outcome>,
+    pub timestamp: i64,
+    pub odds: u64,
+    pub nullifier: [u8; 32],
+    pub is_claimed: bool,
+    pub claimed_amount: u64,
+    pub claimed_timestamp: i64,
+    pub memo: [u8; 16],
+    pub vault: Pubkey,
+    pub sequence: u64,
+    /// Amount this bet added to `vault.total_volume`
+    pub volume_amount: u64,
+    /// Fee taken from this bet at placement, returned if the market is voided
+    pub fee_amount: u64,
+    /// `market.settlement_generation` when the bet was placed
+    pub settlement_generation: u64,
+    /// Whether this bet's stake has voted on a community-resolved market
+    pub resolution_voted: bool,
+    /// Position among the market's bets; leaf index in its entitlement tree
+    pub market_index: u64,
+    /// Index of the outcome this bet backs, below the market's `outcome_count`
+    pub outcome_index: u8,
+    /// LMSR outcome shares the stake bought; one per token where no curve applies
+    pub shares: u64,
+    /// Set by `transfer_position`; claims then go by `bettor`'s signature, not the nullifier
+    pub ownership_transferred: bool,
+    /// Fee rate charged at placement, before any balancing rebate
+    pub fee_basis_points: u16,
+    /// Keeper tip taken from this bet at placement, returned if the market is voided
+    pub keeper_tip: u64,
+}
+
+#[account]
+pub struct AllowedCreator {
+    pub vault: Pubkey,
+    pub creator: Pubkey,
+}
+
+/// Per-creator market accounting, one per vault and creator
+#[account]
+pub struct CreatorStats {
+    pub vault: Pubkey,
+    pub creator: Pubkey,
+    /// Markets created and not yet settled
+    pub open_markets: u32,
+}
+
+/// Labeled outcome set a vault offers for specialized markets, e.g. Home/Away/Draw
+#[account]
+pub struct OutcomeSchema {
+    pub vault: Pubkey,
+    pub schema_id: u8,
+    /// Markets using the schema are created with this `outcome_count`
+    pub outcome_count: u8,
+    /// Zero-padded UTF-8 label per outcome index; only the first `outcome_count` are set
+    pub labels: [[u8; 16]; MAX_OUTCOMES],
+}
+
+#[account]
+pub struct NullifierAccount {
+    pub nullifier: [u8; 32],
+    pub is_used: bool,
+    /// Bump of the `[b"nullifier", nullifier]` PDA this account lives at
+    pub bump: u8,
+}
+
+#[account]
+pub struct VestingEscrow {
+    pub vault: Pubkey,
+    pub market: Pubkey,
+    pub bet: Pubkey,
+    pub beneficiary: Pubkey,
+    pub total_amount: u64,
+    pub released_amount: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub settlement_mint: Pubkey,
+}
+
+#[account]
+pub struct BetReservation {
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub nullifier: [u8; 32],
+    pub is_consumed: bool,
+}
+
+#[account]
+pub struct SurveyBallot {
+    pub market: Pubkey,
+    pub voter: Pubkey,
+    pub choice: SurveyChoice,
+}
+
+// ===== Types =====
+
+/// Serialized as a single-byte discriminant (Yes = 0, No = 1). New variants must only ever
+/// be appended so bets written by older program versions keep their meaning.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+#[repr(u8)]
+pub enum Outcome {
+    Yes = 0,
+    No = 1,
+}
+
+impl Outcome {
+    /// Stable on-chain discriminant of this outcome
+    pub fn discriminant(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Decode a stored discriminant, rejecting values this program version doesn't know
+    pub fn from_discriminant(discriminant: u8) -> Result<Self> {
+        match discriminant {
+            0 => Ok(Outcome::Yes),
+            1 => Ok(Outcome::No),
+            _ => err!(ErrorCode::InvalidOutcome),
+        }
+    }
+
+    /// Position of this outcome in a market's pool vector
+    pub fn index(&self) -> usize {
+        match self {
+            Outcome::Yes => 0,
+            Outcome::No => 1,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct MarketListEntry {
+    pub market: Pubkey,
+    pub id: [u8; 32],
+}
+
+/// A bettor's bets in one market, returned by `get_bettor_history`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BettorHistory {
+    pub bets: Vec<Pubkey>,
+    /// Stake per outcome, indexed by outcome index
+    pub staked_by_outcome: Vec<u64>,
+    pub total_claimed: u64,
+    pub claimed_count: u32,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct MarketParamsCheck {
+    pub valid: bool,
+    pub error_code: u32,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct PayoutQuote {
+    pub stake: u64,
+    pub payout: u64,
+    pub winning_pool: u64,
+    pub total_pool: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct OddsPreview {
+    pub yes_probability: u64,
+    pub outcome_probability: u64,
+    pub effective_price: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct BetQuote {
+    pub fee_basis_points: u16,
+    pub fee_amount: u64,
+    pub keeper_tip: u64,
+    pub stake: u64,
+    pub shares: u64,
+    /// Implied probability the market would move to, in basis points
+    pub implied_probability: u64,
+    /// Gross payout owed if the quoted outcome wins, before the winnings fee
+    pub payout: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct Quotes {
+    pub yes_probability: u64,
+    pub no_probability: u64,
+    pub sum: u64,
+}
+
+/// Who may create markets in a vault
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum CreatorPolicy {
+    /// Any signer may create markets
+    Permissionless,
+    /// Only creators holding an `AllowedCreator` entry for the vault
+    Allowlisted,
+}
+
+/// Lifecycle phase reported by `MarketLifecycleEvent`; see `market_phase`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum MarketPhase {
+    /// Created, before `opening_time`
+    Pending,
+    Open,
+    /// Past `resolution_time`, awaiting the oracle
+    Closed,
+    /// Past `resolution_time`, awaiting the bettor vote of a community-resolved market
+    Voting,
+    Resolved,
+    /// Settled without a winner; every stake is refunded
+    Void,
+}
+
+/// Opinion registered by `survey_vote`; unlike `Outcome` it can abstain
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum SurveyChoice {
+    Yes,
+    No,
+    Abstain,
+}
+
+/// How a market's oracle signs resolutions. New variants must only ever be appended.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum OracleSignatureScheme {
+    /// Native Solana key; `oracle` is the signer's pubkey
+    Ed25519,
+    /// EVM-compatible key; `oracle` holds keccak of the uncompressed public key
+    Secp256k1,
+}
+
+/// How a market's bet fee moves between creation and resolution
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum FeeCurve {
+    /// Every bet pays the base fee
+    Fixed,
+    /// The fee rises linearly from the base fee to the curve maximum at resolution
+    Linear,
+}
+
+/// What the claim-time winnings fee is charged on
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum WinningsFeeBasis {
+    /// The whole payout, stake included
+    Gross,
+    /// Only the payout in excess of the stake
+    Profit,
+}
+
+// ===== Events =====
+
+#[event]
+pub struct MaxFeeUpdated {
+    pub max_fee_basis_points: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultInitialized {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketCreated {
+    pub market: Pubkey,
+    pub market_id: [u8; 32],
+    pub creator: Pubkey,
+    pub resolution_time: i64,
+    pub self_oracle: bool,
+    pub outcome_count: u8,
+    /// Labeled outcome set of the market; the default key when its outcomes are unlabeled
+    pub outcome_schema: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketCloned {
+    pub market: Pubkey,
+    pub source_market: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketTemplateUpdated {
+    pub template: Pubkey,
+    pub oracle: Pubkey,
+    pub default_duration: u64,
+    pub fee_basis_points: u16,
+    pub min_bet_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Off-chain consumers should dedupe on `bet_id`, which is stable across retries of the same bet
+#[event]
+pub struct BetPlaced {
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub bet_id: [u8; 32],
+    pub nullifier: [u8; 32],
+    pub amount: u64,
+    /// Yes/No side of a binary market's bet; `None` when the market has more outcomes
+    pub outcome: Option<Outcome>,
+    pub outcome_index: u8,
+    pub odds: u64,
+    pub memo: [u8; 16],
+    /// Decimals of the market's settlement mint, for formatting `amount`
+    pub mint_decimals: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ResolutionExtended {
+    pub market: Pubkey,
+    pub new_resolution_time: i64,
+    pub extension_count: u8,
+    pub total_extension_seconds: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ResolutionExtensionPolicyUpdated {
+    pub vault: Pubkey,
+    pub resolution_extension_seconds: u64,
+    pub max_resolution_extensions: u8,
+    pub max_total_extension_seconds: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BetReserved {
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub reservation: Pubkey,
+    pub nullifier: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SurveyVoted {
+    pub market: Pubkey,
+    pub voter: Pubkey,
+    pub choice: SurveyChoice,
+    pub stake: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SurveyResults {
+    pub market: Pubkey,
+    pub yes_votes: u64,
+    pub no_votes: u64,
+    pub abstain_votes: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SurveyStakeUpdated {
+    pub vault: Pubkey,
+    pub survey_stake: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultWindDownEntered {
+    pub vault: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultPaused {
+    pub vault: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultUnpaused {
+    pub vault: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NewMarketsPauseUpdated {
+    pub vault: Pubkey,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SettledAccountsClosed {
+    pub market: Pubkey,
+    pub bets_closed: u32,
+    pub market_closed: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BetSplit {
+    pub market: Pubkey,
+    pub original_bet: Pubkey,
+    pub new_bet: Pubkey,
+    pub remaining_amount: u64,
+    pub split_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PositionTransferred {
+    pub market: Pubkey,
+    pub bet: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketResolved {
+    pub market: Pubkey,
+    /// Encodes as the `Outcome` discriminant for Yes/No markets
+    pub winning_index: u8,
+    pub total_yes: u64,
+    pub total_no: u64,
+    /// Stake on each outcome, indexed by outcome index
+    pub outcome_totals: Vec<u64>,
+    /// Payout per unit staked on the winning side, in basis points (0 when `is_void`)
+    pub winning_payout_ratio: u64,
+    /// No stake on the winning side: every bet is refunded at face value
+    pub is_void: bool,
+    pub source_ref: [u8; 32],
+    pub reference_price: i64,
+    pub reference_expo: i32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketResolvedProbabilistic {
+    pub market: Pubkey,
+    pub yes_probability: u64,
+    pub total_yes: u64,
+    pub total_no: u64,
+    pub timestamp: i64,
+}
+
+/// One chronological stream of phase transitions per market, alongside the specific
+/// create/resolve/void events
+#[event]
+pub struct MarketLifecycleEvent {
+    pub market: Pubkey,
+    pub market_id: [u8; 32],
+    pub from_phase: Option<MarketPhase>,
+    pub to_phase: MarketPhase,
+    pub actor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ResolutionOverridden {
+    pub market: Pubkey,
+    pub previous_outcome: Outcome,
+    pub corrected_outcome: Outcome,
+    pub authority: Pubkey,
+    pub claims_unlock_time: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityOverrideWindowUpdated {
+    pub vault: Pubkey,
+    pub authority_override_window_seconds: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ResolutionVoteSubmitted {
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub outcome_index: u8,
+    pub weight: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeConfigUpdated {
+    pub vault: Pubkey,
+    pub challenge_window_seconds: u64,
+    pub dispute_bond_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeRaised {
+    pub market: Pubkey,
+    pub disputer: Pubkey,
+    pub bond: u64,
+    /// Outcome the oracle resolved to, under challenge
+    pub winning_index: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub disputer: Pubkey,
+    /// The dispute was upheld: the outcome was overridden and the bond refunded
+    pub upheld: bool,
+    pub previous_index: u8,
+    pub winning_index: u8,
+    pub bond: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimWindowUpdated {
+    pub vault: Pubkey,
+    pub claim_window_seconds: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnclaimedSwept {
+    pub market: Pubkey,
+    pub treasury: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketCancelled {
+    pub market: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub total_refundable: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CancellationGraceUpdated {
+    pub vault: Pubkey,
+    pub cancellation_grace_seconds: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketVoided {
+    pub market: Pubkey,
+    /// Stake-weighted votes per outcome, indexed by outcome index
+    pub votes: Vec<u64>,
+    pub total_refundable: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CommunityResolutionPolicyUpdated {
+    pub vault: Pubkey,
+    pub community_quorum_basis_points: u16,
+    pub community_vote_window_seconds: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultBalanceSnapshot {
+    pub market: Pubkey,
+    /// Vault token balance read after the payout, shared across all markets of the vault
+    pub vault_balance: u64,
+    pub total_claimed: u64,
+    pub total_claimable: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SafeModeActivated {
+    pub market: Pubkey,
+    pub total_claimable: u64,
+    pub available: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WinningsClaimed {
+    pub market: Pubkey,
+    pub claimant: Pubkey,
+    pub amount: u64,
+    pub gross_winnings: u64,
+    pub fee_taken: u64,
+    /// Bet-time fee, plus the keeper tip of a voided market, returned with the refund
+    pub fee_refunded: u64,
+    pub original_stake: u64,
+    /// No-contest refund of the original stake rather than a win
+    pub is_refund: bool,
+    /// Decimals of the market's settlement mint, for formatting the amounts above
+    pub mint_decimals: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WinningsVested {
+    pub market: Pubkey,
+    pub claimant: Pubkey,
+    pub escrow: Pubkey,
+    pub amount: u64,
+    pub end_time: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestedReleased {
+    pub escrow: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub total_released: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LiquidityAdded {
+    pub market: Pubkey,
+    pub provider: Pubkey,
+    pub amount: u64,
+    pub lp_tokens: u64,
+    pub total_liquidity_locked: u64,
+    pub implied_probability: u64,
+    /// Decimals of the market's settlement mint, for formatting `amount`
+    pub mint_decimals: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LiquidityRemoved {
+    pub market: Pubkey,
+    pub provider: Pubkey,
+    /// Settlement tokens paid out, after any early-exit fee
+    pub amount: u64,
+    pub lp_tokens: u64,
+    pub exit_fee: u64,
+    pub total_liquidity_locked: u64,
+    pub implied_probability: u64,
+    pub mint_decimals: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DonationConfigUpdated {
+    pub vault: Pubkey,
+    pub donation_basis_points: u16,
+    pub donation_recipient: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SettlementMintAdded {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PrivacyPolicyUpdated {
+    pub vault: Pubkey,
+    pub privacy_enabled: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SelfOraclePolicyUpdated {
+    pub vault: Pubkey,
+    pub allow_self_oracle: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MaxTotalLockupUpdated {
+    pub vault: Pubkey,
+    pub max_total_lockup_seconds: u64,
+    pub configured_lockup_seconds: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MaxOpenMarketsPerCreatorUpdated {
+    pub vault: Pubkey,
+    pub max_open_markets_per_creator: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MaxLiquidityUpdated {
+    pub vault: Pubkey,
+    pub max_liquidity: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MinInitialLiquidityUpdated {
+    pub vault: Pubkey,
+    pub min_initial_liquidity: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MinResolutionOffsetUpdated {
+    pub vault: Pubkey,
+    pub min_resolution_offset_seconds: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreatorPolicyUpdated {
+    pub vault: Pubkey,
+    pub creator_policy: CreatorPolicy,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreatorAllowlistUpdated {
+    pub vault: Pubkey,
+    pub creator: Pubkey,
+    pub allowed: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OutcomeSchemaCreated {
+    pub vault: Pubkey,
+    pub schema: Pubkey,
+    pub schema_id: u8,
+    pub labels: Vec<[u8; 16]>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeatureFlagsUpdated {
+    pub vault: Pubkey,
+    pub program_version: u16,
+    pub feature_flags: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensRescued {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultStats {
+    pub vault: Pubkey,
+    pub total_volume: u64,
+    pub total_fees_collected: u64,
+    pub total_fees_withdrawn: u64,
+    pub pending_donations: u64,
+    pub total_donations_distributed: u64,
+    pub total_liquidity_locked: u64,
+    pub market_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceDeposited {
+    pub market: Pubkey,
+    pub amount: u64,
+    pub insurance_pool: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SettlementProgress {
+    pub market: Pubkey,
+    pub start: u64,
+    pub settlement_cursor: u64,
+    pub bet_count: u64,
+    pub settlement_complete: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EntitlementRootCommitted {
+    pub market: Pubkey,
+    pub entitlement_root: [u8; 32],
+    pub bet_count: u64,
+    pub settlement_generation: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VolumeReconciled {
+    pub vault: Pubkey,
+    pub recorded_volume: u64,
+    pub reconciled_volume: u64,
+    pub bet_count: u64,
+    pub overwritten: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MaxMerkleDepthUpdated {
+    pub vault: Pubkey,
+    pub max_merkle_depth: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OddsScaledMinBetUpdated {
+    pub vault: Pubkey,
+    pub threshold_bps: u16,
+    pub max_multiplier: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BalancingRebateUpdated {
+    pub vault: Pubkey,
+    pub balancing_rebate_basis_points: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct KeeperTipUpdated {
+    pub vault: Pubkey,
+    pub keeper_tip_basis_points: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct KeeperTipPaid {
+    pub market: Pubkey,
+    pub keeper: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BurnConfigUpdated {
+    pub vault: Pubkey,
+    pub burn_basis_points: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LoserPoolBurned {
+    pub market: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OracleSignatureSchemeUpdated {
+    pub vault: Pubkey,
+    pub oracle_signature_scheme: OracleSignatureScheme,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WinningsFeeUpdated {
+    pub vault: Pubkey,
+    pub winnings_fee_basis_points: u16,
+    pub winnings_fee_basis: WinningsFeeBasis,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EarlyExitFeeUpdated {
+    pub vault: Pubkey,
+    pub early_exit_fee_basis_points: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesWithdrawn {
+    pub vault: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub total_fees_withdrawn: u64,
+    /// Collected fees still available to withdraw
+    pub remaining: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DonationsDistributed {
+    pub vault: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+// ===== Errors =====
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Invalid fee basis points")]
+    InvalidFee,
+    #[msg("Invalid minimum bet amount")]
+    InvalidMinBet,
+    #[msg("Invalid resolution time")]
+    InvalidResolutionTime,
+    #[msg("Market already resolved")]
+    MarketResolved,
+    #[msg("Market is closed for betting")]
+    MarketClosed,
+    #[msg("Bet amount too small")]
+    BetTooSmall,
+    #[msg("Nullifier already used")]
+    NullifierAlreadyUsed,
+    #[msg("Market already resolved")]
+    MarketAlreadyResolved,
+    #[msg("Too early to resolve market")]
+    TooEarlyToResolve,
+    #[msg("Unauthorized oracle")]
+    UnauthorizedOracle,
+    #[msg("Invalid commit-reveal")]
+    InvalidReveal,
+    #[msg("Market not resolved yet")]
+    MarketNotResolved,
+    #[msg("Winnings already claimed")]
+    AlreadyClaimed,
+    #[msg("Not a winning bet")]
+    NotWinner,
+    #[msg("Amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Template does not belong to this vault")]
+    InvalidTemplate,
+    #[msg("Claim would exceed the market's total claimable amount")]
+    ClaimExceedsClaimable,
+    #[msg("Invalid donation configuration")]
+    InvalidDonationConfig,
+    #[msg("Recipient does not match the configured donation recipient")]
+    InvalidDonationRecipient,
+    #[msg("Market is not probabilistic")]
+    NotProbabilisticMarket,
+    #[msg("Probability out of bounds")]
+    InvalidProbability,
+    #[msg("Opening time must precede resolution time")]
+    InvalidOpeningTime,
+    #[msg("Market is not open for betting yet")]
+    MarketNotOpenYet,
+    #[msg("Nullifier is not bound to this market")]
+    InvalidNullifier,
+    #[msg("Bet does not belong to this market")]
+    BetMarketMismatch,
+    #[msg("Split amount must be between zero and the bet amount")]
+    InvalidSplitAmount,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Market is not tradable until every outcome is seeded")]
+    MarketNotTradable,
+    #[msg("Market does not belong to this vault")]
+    InvalidMarketVault,
+    #[msg("Reservation does not match this market and bettor")]
+    InvalidReservation,
+    #[msg("Reservation already consumed")]
+    ReservationConsumed,
+    #[msg("Recorded bet amounts do not match market pools")]
+    PoolConservationViolated,
+    #[msg("Requested page exceeds the maximum page size")]
+    PageTooLarge,
+    #[msg("Page accounts do not cover the requested sequence range")]
+    IncompletePage,
+    #[msg("Invalid insurance configuration")]
+    InvalidInsurance,
+    #[msg("Insured obligations exceed the insurance pool")]
+    InsufficientInsurance,
+    #[msg("Unknown outcome")]
+    InvalidOutcome,
+    #[msg("Price reference account missing or invalid")]
+    InvalidPriceReference,
+    #[msg("Price reference is stale")]
+    StalePriceReference,
+    #[msg("Resolution extensions are disabled")]
+    ExtensionsDisabled,
+    #[msg("Resolution extension limit reached")]
+    ExtensionLimitReached,
+    #[msg("Vault forbids markets where the creator is the oracle")]
+    SelfOracleNotAllowed,
+    #[msg("Only foreign-mint tokens can be rescued")]
+    CannotRescueSettlementMint,
+    #[msg("Resolution time is closer than the vault's minimum offset")]
+    ResolutionTooSoon,
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+    #[msg("Nothing has vested yet")]
+    NothingVested,
+    #[msg("Invalid merkle proof")]
+    InvalidMerkleProof,
+    #[msg("Feature is not enabled for this vault")]
+    FeatureDisabled,
+    #[msg("Settlement mint is not accepted by this vault")]
+    UnsupportedSettlementMint,
+    #[msg("Token account mint does not match the market's settlement mint")]
+    SettlementMintMismatch,
+    #[msg("No free settlement mint slot")]
+    SettlementMintLimitReached,
+    #[msg("Creator is not allowed to create markets in this vault")]
+    CreatorNotAllowed,
+    #[msg("Burn share must not exceed 10000 basis points")]
+    InvalidBurnConfig,
+    #[msg("Burn requires the market's settlement mint and a vault-owned token account")]
+    InvalidBurnAccount,
+    #[msg("Vault token balance is too low")]
+    InsufficientVaultBalance,
+    #[msg("Merkle proof exceeds the configured maximum depth")]
+    ProofTooDeep,
+    #[msg("Maximum merkle depth must be non-zero")]
+    InvalidMerkleDepth,
+    #[msg("Reconciliation batch does not continue the current pass")]
+    ReconciliationOutOfOrder,
+    #[msg("Reconciliation has not covered every bet")]
+    ReconciliationIncomplete,
+    #[msg("Initial liquidity is below the vault minimum")]
+    InsufficientInitialLiquidity,
+    #[msg("Token accounts are required to seed initial liquidity")]
+    MissingLiquidityAccounts,
+    #[msg("Private markets require a nullifier account")]
+    NullifierAccountRequired,
+    #[msg("Keeper tips require a resolver token account in the market's mint")]
+    InvalidKeeperAccount,
+    #[msg("Liquidity would exceed the market's cap")]
+    LiquidityCapReached,
+    #[msg("Liquidity cap must not be below the minimum initial liquidity")]
+    InvalidLiquidityCap,
+    #[msg("Survey voting is disabled for this vault")]
+    SurveyDisabled,
+    #[msg("Cumulative resolution extension ceiling reached")]
+    ExtensionCeilingReached,
+    #[msg("Claim batch must hold 1..=MAX_CLAIM_BATCH_SIZE distinct market/bet pairs with one proof each")]
+    InvalidClaimBatch,
+    #[msg("Withdrawal exceeds the LP's remaining settlement entitlement")]
+    LpEntitlementExceeded,
+    #[msg("New market creation is paused for this vault")]
+    NewMarketsPaused,
+    #[msg("Collected fees cannot cover the voided bet's fee refund")]
+    InsufficientFeesForRefund,
+    #[msg("Market settlement changed since this bet was placed")]
+    StaleSettlement,
+    #[msg("Bettor history must list only the bettor's own bets, each once")]
+    InvalidBettorHistory,
+    #[msg("Token account is not owned by the vault")]
+    InvalidVaultTokenAccount,
+    #[msg("Oracle signature does not recover to the market's oracle")]
+    InvalidOracleSignature,
+    #[msg("Vault is winding down; only exits are allowed")]
+    VaultWindingDown,
+    #[msg("Invalid community resolution: market must be binary and uninsured, quorum 1..=10000 bps")]
+    InvalidCommunityMarket,
+    #[msg("Market is not community-resolved")]
+    NotCommunityResolved,
+    #[msg("Community-resolved markets settle by bettor vote, not by oracle")]
+    CommunityResolvedMarket,
+    #[msg("Resolution vote window has closed")]
+    ResolutionVotingClosed,
+    #[msg("Resolution vote window is still open")]
+    ResolutionVotingOpen,
+    #[msg("This bet has already voted")]
+    AlreadyVoted,
+    #[msg("Creator has reached the vault's open-market limit")]
+    CreatorMarketLimit,
+    #[msg("Market holds a creator slot; pass the creator's stats account")]
+    CreatorStatsRequired,
+    #[msg("Winnings do not cover the new bet")]
+    InsufficientWinnings,
+    #[msg("Market has more bets than its entitlement tree holds")]
+    EntitlementTreeFull,
+    #[msg("Settlement batch does not start at the market's settlement cursor")]
+    SettlementBatchOutOfOrder,
+    #[msg("Settlement pass already complete for this settlement")]
+    SettlementAlreadyComplete,
+    #[msg("Entitlement root not committed yet")]
+    EntitlementsNotCommitted,
+    #[msg("Entitlement proof does not match the committed root")]
+    InvalidEntitlementProof,
+    #[msg("Claims are locked until the authority override window ends")]
+    ClaimsLocked,
+    #[msg("Authority override window has closed")]
+    OverrideWindowClosed,
+    #[msg("Only oracle-resolved binary markets without a burn can be overridden")]
+    OverrideNotAllowed,
+    #[msg("Settlement pass has not processed every bet yet")]
+    SettlementIncomplete,
+    #[msg("Odds scaling threshold must be below 10000 bps with a multiplier of at least 1")]
+    InvalidOddsScaling,
+    #[msg("Bet account already holds a bet")]
+    BetAccountInUse,
+    #[msg("Amount exceeds the collected fees not yet withdrawn")]
+    InsufficientFees,
+    #[msg("Fee destination must be owned by the vault authority")]
+    InvalidFeeDestination,
+    #[msg("Configured windows would lock funds past the vault's lockup horizon")]
+    LockupHorizonExceeded,
+    #[msg("Vault is paused; only resolution and exits are allowed")]
+    VaultPaused,
+    #[msg("Vault is already in the requested pause state")]
+    PauseStateUnchanged,
+    #[msg("Market can only be cancelled once its resolution grace period has passed")]
+    CancellationGraceActive,
+    #[msg("Outcome count must be 2..=MAX_OUTCOMES, and 2 for binary-only market features")]
+    InvalidOutcomeCount,
+    #[msg("Only Yes/No markets support this instruction")]
+    BinaryMarketOnly,
+    #[msg("The market's claim window has expired")]
+    ClaimWindowExpired,
+    #[msg("The market's claim window is still open")]
+    ClaimWindowOpen,
+    #[msg("Treasury account must be owned by the vault authority")]
+    InvalidTreasuryAccount,
+    #[msg("Market has no unclaimed balance to sweep")]
+    NothingToSweep,
+    #[msg("LP mint must be the market's, minted only by the vault PDA")]
+    InvalidLpMint,
+    #[msg("LP token account must hold the market's LP mint and belong to the provider")]
+    InvalidLpTokenAccount,
+    #[msg("Position must move to a different, non-default owner")]
+    InvalidPositionOwner,
+    #[msg("Market resolution is within its challenge window or under dispute")]
+    MarketUnderChallenge,
+    #[msg("Challenge window for this resolution has closed")]
+    DisputeWindowClosed,
+    #[msg("Market resolution is already disputed")]
+    MarketAlreadyDisputed,
+    #[msg("Disputes are disabled for this vault")]
+    DisputesDisabled,
+    #[msg("Market has no pending dispute")]
+    MarketNotDisputed,
+    #[msg("Bond refunds require a token account owned by the disputer")]
+    InvalidDisputerAccount,
+    #[msg("Outcome schema must belong to the vault and label each outcome distinctly")]
+    InvalidOutcomeSchema,
+    #[msg("Only oracle resolutions to a single outcome can be disputed")]
+    DisputeNotAllowed,
+}
+
+// ===== Context Structs =====
+
+#[derive(Accounts)]
+pub struct InitializeProgramConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + size_of::<ProgramConfig>(),
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxFeeBasisPoints<'info> {
+    #[account(mut, seeds = [b"config"], bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeVault<'info> {
+    #[account(init, payer = authority, space = 8 + size_of::<Vault>())]
+    pub vault: Account<'info, Vault>,
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub settlement_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMarket<'info> {
+    #[account(init, payer = creator, space = 8 + size_of::<Market>())]
+    pub market: Account<'info, Market>,
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        seeds = [b"allowed_creator", vault.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub creator_allowlist_entry: Option<Account<'info, AllowedCreator>>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + size_of::<CreatorStats>(),
+        seeds = [b"creator_stats", vault.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub creator_stats: Account<'info, CreatorStats>,
+    pub outcome_schema: Option<Account<'info, OutcomeSchema>>,
+    #[account(mut)]
+    pub creator_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub lp_mint: Option<Account<'info, Mint>>,
+    #[account(mut)]
+    pub creator_lp_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ValidateMarketParams<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub creator: Signer<'info>,
+    #[account(
+        seeds = [b"allowed_creator", vault.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub creator_allowlist_entry: Option<Account<'info, AllowedCreator>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMarketTemplate<'info> {
+    #[account(init, payer = authority, space = 8 + size_of::<MarketTemplate>())]
+    pub template: Account<'info, MarketTemplate>,
+    pub vault: Account<'info, Vault>,
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMarketTemplate<'info> {
+    #[account(mut)]
+    pub template: Account<'info, MarketTemplate>,
+    pub vault: Account<'info, Vault>,
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMarketFromTemplate<'info> {
+    #[account(init, payer = creator, space = 8 + size_of::<Market>())]
+    pub market: Account<'info, Market>,
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub template: Account<'info, MarketTemplate>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        seeds = [b"allowed_creator", vault.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub creator_allowlist_entry: Option<Account<'info, AllowedCreator>>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + size_of::<CreatorStats>(),
+        seeds = [b"creator_stats", vault.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub creator_stats: Account<'info, CreatorStats>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloneMarket<'info> {
+    #[account(init, payer = creator, space = 8 + size_of::<Market>())]
+    pub market: Account<'info, Market>,
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub source_market: Account<'info, Market>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        seeds = [b"allowed_creator", vault.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub creator_allowlist_entry: Option<Account<'info, AllowedCreator>>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + size_of::<CreatorStats>(),
+        seeds = [b"creator_stats", vault.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub creator_stats: Account<'info, CreatorStats>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, outcome_index: u8, proof: Vec<u8>, nullifier: [u8; 32])]
+pub struct PlaceBet<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(init, payer = bettor, space = 8 + size_of::<BetAccount>())]
+    pub bet_account: Account<'info, BetAccount>,
+    /// Created and consumed atomically here at the nullifier's PDA: `init` rejects any
+    /// pre-existing account, so a reused nullifier fails and a griefer cannot pre-mark a
+    /// victim's nullifier as used outside of a real bet. Omitted for markets with privacy
+    /// disabled.
+    #[account(
+        init,
+        payer = bettor,
+        space = 8 + size_of::<NullifierAccount>(),
+        seeds = [b"nullifier", nullifier.as_ref()],
+        bump
+    )]
+    pub nullifier_account: Option<Account<'info, NullifierAccount>>,
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+    #[account(mut)]
+    pub bettor_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proof: Vec<u8>, nullifier: [u8; 32])]
+pub struct VerifyAndReserve<'info> {
+    pub vault: Account<'info, Vault>,
+    pub market: Account<'info, Market>,
+    #[account(
+        init,
+        payer = bettor,
+        space = 8 + size_of::<NullifierAccount>(),
+        seeds = [b"nullifier", nullifier.as_ref()],
+        bump
+    )]
+    pub nullifier_account: Account<'info, NullifierAccount>,
+    #[account(init, payer = bettor, space = 8 + size_of::<BetReservation>())]
+    pub reservation: Account<'info, BetReservation>,
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceBetReserved<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut)]
+    pub reservation: Account<'info, BetReservation>,
+    #[account(init, payer = bettor, space = 8 + size_of::<BetAccount>())]
+    pub bet_account: Account<'info, BetAccount>,
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+    #[account(mut)]
+    pub bettor_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendResolution<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SurveyVote<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    /// One ballot per voter per market: `init` rejects a second vote
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + size_of::<SurveyBallot>(),
+        seeds = [b"survey", market.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub survey_ballot: Account<'info, SurveyBallot>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    #[account(mut)]
+    pub voter_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SplitBet<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut)]
+    pub bet_account: Account<'info, BetAccount>,
+    #[account(init, payer = bettor, space = 8 + size_of::<BetAccount>())]
+    pub new_bet_account: Account<'info, BetAccount>,
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferPosition<'info> {
+    pub market: Account<'info, Market>,
+    #[account(mut)]
+    pub bet_account: Account<'info, BetAccount>,
+    pub bettor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveMarket<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    pub vault: Account<'info, Vault>,
+    pub oracle: Signer<'info>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub settlement_mint: Account<'info, Mint>,
+    /// Receives the market's keeper tips; required only when tips have accrued
+    #[account(mut)]
+    pub resolver_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    /// CHECK: validated against `market.price_reference_account` and parsed as a Pyth feed
+    pub price_reference: Option<UncheckedAccount<'info>>,
+    /// CHECK: the Instructions sysvar, pinned by address; searched for the Ed25519 program's
+    /// verification of the oracle signature
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    /// Required once the market settles if it holds a slot in its creator's stats
+    #[account(
+        mut,
+        seeds = [b"creator_stats", vault.key().as_ref(), market.creator.as_ref()],
+        bump
+    )]
+    pub creator_stats: Option<Account<'info, CreatorStats>>,
+}
+
+#[derive(Accounts)]
+pub struct OverrideResolution<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAuthorityOverrideWindow<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitResolutionVote<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut)]
+    pub bet_account: Account<'info, BetAccount>,
+    pub bettor: Signer<'info>,
+    /// Read to size safe mode if this vote finalizes the market
+    pub vault_token_account: Account<'info, TokenAccount>,
+    /// Required once the market settles if it holds a slot in its creator's stats
+    #[account(
+        mut,
+        seeds = [b"creator_stats", vault.key().as_ref(), market.creator.as_ref()],
+        bump
+    )]
+    pub creator_stats: Option<Account<'info, CreatorStats>>,
+}
+
+#[derive(Accounts)]
+pub struct VoidCommunityMarket<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    pub vault_token_account: Account<'info, TokenAccount>,
+    /// Anyone; recorded as the lifecycle actor
+    pub caller: Signer<'info>,
+    /// Required once the market settles if it holds a slot in its creator's stats
+    #[account(
+        mut,
+        seeds = [b"creator_stats", vault.key().as_ref(), market.creator.as_ref()],
+        bump
+    )]
+    pub creator_stats: Option<Account<'info, CreatorStats>>,
+}
+
+#[derive(Accounts)]
+pub struct SetClaimWindow<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDisputeConfig<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RaiseDispute<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    pub bet_account: Account<'info, BetAccount>,
+    pub disputer: Signer<'info>,
+    #[account(mut)]
+    pub disputer_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    /// Receives the bond back when the dispute is upheld
+    #[account(mut)]
+    pub disputer_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SweepUnclaimed<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelMarket<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    pub vault_token_account: Account<'info, TokenAccount>,
+    /// The vault authority or the market creator
+    pub caller: Signer<'info>,
+    /// Required once the market settles if it holds a slot in its creator's stats
+    #[account(
+        mut,
+        seeds = [b"creator_stats", vault.key().as_ref(), market.creator.as_ref()],
+        bump
+    )]
+    pub creator_stats: Option<Account<'info, CreatorStats>>,
+}
+
+#[derive(Accounts)]
+pub struct SetCancellationGrace<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCommunityResolutionPolicy<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimWinnings<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut)]
+    pub bet_account: Account<'info, BetAccount>,
+    pub claimant: Signer<'info>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub claimant_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    amount: u64,
+    outcome_index: u8,
+    claim_proof: Vec<u8>,
+    allowlist_proof: Vec<u8>,
+    nullifier: [u8; 32],
+)]
+pub struct ClaimAndBet<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut)]
+    pub bet_account: Account<'info, BetAccount>,
+    /// Open market receiving the rolled-over stake; settled markets fail the bet window check
+    #[account(mut)]
+    pub new_market: Account<'info, Market>,
+    #[account(init, payer = claimant, space = 8 + size_of::<BetAccount>())]
+    pub new_bet_account: Account<'info, BetAccount>,
+    /// Omitted when `new_market` has privacy disabled
+    #[account(
+        init,
+        payer = claimant,
+        space = 8 + size_of::<NullifierAccount>(),
+        seeds = [b"nullifier", nullifier.as_ref()],
+        bump
+    )]
+    pub nullifier_account: Option<Account<'info, NullifierAccount>>,
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub claimant_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimWinningsBatch<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub claimant: Signer<'info>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub claimant_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimWinningsVested<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut)]
+    pub bet_account: Account<'info, BetAccount>,
+    #[account(
+        init,
+        payer = claimant,
+        space = 8 + size_of::<VestingEscrow>(),
+        seeds = [b"vesting", bet_account.key().as_ref()],
+        bump
+    )]
+    pub vesting_escrow: Account<'info, VestingEscrow>,
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseVested<'info> {
+    pub market: Account<'info, Market>,
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub vesting_escrow: Account<'info, VestingEscrow>,
+    pub beneficiary: Signer<'info>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub beneficiary_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct AddLiquidity<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub provider: Signer<'info>,
+    #[account(mut)]
+    pub provider_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub provider_lp_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveLiquidity<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub provider: Signer<'info>,
+    #[account(mut)]
+    pub provider_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub provider_lp_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
 }
 
-fn verify_merkle_proof(proof: &[u8], root: [u8; 32], leaf: Pubkey) -> Result<()> {
-    // Merkle proof verification logic
-    // This would implement standard merkle tree verification
-    Ok(())
+#[derive(Accounts)]
+pub struct DepositInsurance<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    pub creator: Signer<'info>,
+    #[account(mut)]
+    pub creator_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
 }
 
-fn verify_oracle_signature(
-    signature: &[u8],
-    market_id: &[u8; 32],
-    outcome: Outcome,
-    oracle: &Pubkey,
-) -> Result<()> {
-    // Ed25519 signature verification
-    Ok(())
+#[derive(Accounts)]
+pub struct SetDonationConfig<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
 }
 
-fn verify_zk_proof(proof: &[u8], nullifier: &[u8; 32], claimant: &Pubkey) -> Result<()> {
-    // Zero-knowledge proof verification
-    // Would integrate with a ZK library like Groth16 or PLONK
-    Ok(())
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub destination_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
 }
 
-// ===== Account Structures =====
+#[derive(Accounts)]
+pub struct DistributeDonations<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
 
-#[account]
-pub struct Vault {
-    pub authority: Pubkey,
-    pub merkle_root: [u8; 32],
-    pub fee_basis_points: u16,
-    pub min_bet_amount: u64,
-    pub total_volume: u64,
-    pub total_fees_collected: u64,
-    pub nonce: u8,
-    pub is_paused: bool,
-    pub creation_timestamp: i64,
+#[derive(Accounts)]
+pub struct SetMaxMerkleDepth<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
 }
 
-#[account]
-pub struct Market {
-    pub id: [u8; 32],
-    pub vault: Pubkey,
-    pub creator: Pubkey,
-    pub oracle: Pubkey,
-    pub resolution_time: i64,
-    pub commitment_hash: [u8; 32],
-    pub total_yes_amount: u64,
-    pub total_no_amount: u64,
-    pub is_resolved: bool,
-    pub winning_outcome: Option<Outcome>,
-    pub creation_timestamp: i64,
-    pub resolution_timestamp: i64,
-    pub implied_probability: u64,
-    pub liquidity_locked: u64,
+#[derive(Accounts)]
+pub struct SetOddsScaledMinBet<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
 }
 
-#[account]
-pub struct BetAccount {
-    pub market: Pubkey,
-    pub bettor: Pubkey,
-    pub amount: u64,
-    pub outcome: Outcome,
-    pub timestamp: i64,
-    pub odds: u64,
-    pub nullifier: [u8; 32],
-    pub is_claimed: bool,
-    pub claimed_amount: u64,
-    pub claimed_timestamp: i64,
+#[derive(Accounts)]
+pub struct SetBalancingRebate<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
 }
 
-#[account]
-pub struct NullifierAccount {
-    pub nullifier: [u8; 32],
-    pub is_used: bool,
+#[derive(Accounts)]
+pub struct SetKeeperTip<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
 }
 
-// ===== Types =====
+#[derive(Accounts)]
+pub struct SetBurnBasisPoints<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
-pub enum Outcome {
-    Yes,
-    No,
+#[derive(Accounts)]
+pub struct SetOracleSignatureScheme<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
 }
 
-// ===== Events =====
+#[derive(Accounts)]
+pub struct SetWinningsFee<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub authority: Signer<'info>,
+}
 
-#[event]
-pub struct VaultInitialized {
-    pub vault: Pubkey,
-    pub authority: Pubkey,
-    pub merkle_root: [u8; 32],
-    pub timestamp: i64,
+#[derive(Accounts)]
+pub struct SetEarlyExitFee<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub authority: Signer<'info>,
 }
 
-#[event]
-pub struct MarketCreated {
-    pub market: Pubkey,
-    pub market_id: [u8; 32],
-    pub creator: Pubkey,
-    pub resolution_time: i64,
-    pub timestamp: i64,
+#[derive(Accounts)]
+pub struct SetResolutionExtensionPolicy<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
 }
 
-#[event]
-pub struct BetPlaced {
-    pub market: Pubkey,
-    pub bettor: Pubkey,
-    pub amount: u64,
-    pub outcome: Outcome,
-    pub odds: u64,
-    pub timestamp: i64,
+#[derive(Accounts)]
+pub struct SetPrivacyEnabled<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
 }
 
-#[event]
-pub struct MarketResolved {
-    pub market: Pubkey,
-    pub winning_outcome: Outcome,
-    pub total_yes: u64,
-    pub total_no: u64,
-    pub timestamp: i64,
+#[derive(Accounts)]
+pub struct SetAllowSelfOracle<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
 }
 
-#[event]
-pub struct WinningsClaimed {
-    pub market: Pubkey,
-    pub claimant: Pubkey,
-    pub amount: u64,
-    pub timestamp: i64,
+#[derive(Accounts)]
+pub struct AddSettlementMint<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+    pub mint: Account<'info, Mint>,
 }
 
-#[event]
-pub struct LiquidityAdded {
-    pub market: Pubkey,
-    pub provider: Pubkey,
-    pub amount: u64,
-    pub lp_tokens: u64,
-    pub timestamp: i64,
+#[derive(Accounts)]
+pub struct SetSurveyStake<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
 }
 
-// ===== Errors =====
+#[derive(Accounts)]
+pub struct EnterWindDown<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Invalid fee basis points")]
-    InvalidFee,
-    #[msg("Invalid minimum bet amount")]
-    InvalidMinBet,
-    #[msg("Invalid resolution time")]
-    InvalidResolutionTime,
-    #[msg("Market already resolved")]
-    MarketResolved,
-    #[msg("Market is closed for betting")]
-    MarketClosed,
-    #[msg("Bet amount too small")]
-    BetTooSmall,
-    #[msg("Nullifier already used")]
-    NullifierAlreadyUsed,
-    #[msg("Market already resolved")]
-    MarketAlreadyResolved,
-    #[msg("Too early to resolve market")]
-    TooEarlyToResolve,
-    #[msg("Unauthorized oracle")]
-    UnauthorizedOracle,
-    #[msg("Invalid commit-reveal")]
-    InvalidReveal,
-    #[msg("Market not resolved yet")]
-    MarketNotResolved,
-    #[msg("Winnings already claimed")]
-    AlreadyClaimed,
-    #[msg("Not a winning bet")]
-    NotWinner,
+#[derive(Accounts)]
+pub struct SetVaultPaused<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetNewMarketsPaused<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxTotalLockup<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxOpenMarketsPerCreator<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxLiquidity<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
 }
 
-// ===== Context Structs =====
+#[derive(Accounts)]
+pub struct SetMinInitialLiquidity<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
 
 #[derive(Accounts)]
-pub struct InitializeVault<'info> {
-    #[account(init, payer = authority, space = 8 + size_of::<Vault>())]
+pub struct SetMinResolutionOffset<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeatureFlags<'info> {
+    #[account(mut)]
     pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCreatorPolicy<'info> {
     #[account(mut)]
+    pub vault: Account<'info, Vault>,
     pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct CreateMarket<'info> {
-    #[account(init, payer = creator, space = 8 + size_of::<Market>())]
-    pub market: Account<'info, Market>,
+#[instruction(creator: Pubkey)]
+pub struct AddAllowedCreator<'info> {
     pub vault: Account<'info, Vault>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + size_of::<AllowedCreator>(),
+        seeds = [b"allowed_creator", vault.key().as_ref(), creator.as_ref()],
+        bump
+    )]
+    pub allowed_creator: Account<'info, AllowedCreator>,
     #[account(mut)]
-    pub creator: Signer<'info>,
+    pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct PlaceBet<'info> {
+pub struct RemoveAllowedCreator<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"allowed_creator", vault.key().as_ref(), allowed_creator.creator.as_ref()],
+        bump
+    )]
+    pub allowed_creator: Account<'info, AllowedCreator>,
     #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(schema_id: u8)]
+pub struct CreateOutcomeSchema<'info> {
     pub vault: Account<'info, Vault>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + size_of::<OutcomeSchema>(),
+        seeds = [b"outcome_schema", vault.key().as_ref(), &[schema_id]],
+        bump
+    )]
+    pub outcome_schema: Account<'info, OutcomeSchema>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CleanupSettled<'info> {
     #[account(mut)]
     pub market: Account<'info, Market>,
-    #[account(init, payer = bettor, space = 8 + size_of::<BetAccount>())]
-    pub bet_account: Account<'info, BetAccount>,
-    #[account(init, payer = bettor, space = 8 + size_of::<NullifierAccount>())]
-    pub nullifier_account: Account<'info, NullifierAccount>,
+    /// CHECK: must match `market.creator`; receives the market's rent when it is closed
     #[account(mut)]
-    pub bettor: Signer<'info>,
+    pub market_creator: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RescueTokens<'info> {
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
     #[account(mut)]
-    pub bettor_token_account: Account<'info, TokenAccount>,
+    pub source_token_account: Account<'info, TokenAccount>,
     #[account(mut)]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub destination_token_account: Account<'info, TokenAccount>,
     pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ResolveMarket<'info> {
+pub struct EmitVaultStats<'info> {
+    pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct HealthCheck<'info> {
+    pub vault: Account<'info, Vault>,
+    pub vault_token_account: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct CommitEntitlements<'info> {
     #[account(mut)]
     pub market: Account<'info, Market>,
-    pub oracle: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimWinnings<'info> {
+pub struct VerifyEntitlementProof<'info> {
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct ReconcileVolume<'info> {
     #[account(mut)]
     pub vault: Account<'info, Vault>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetMarketListPage<'info> {
+    pub vault: Account<'info, Vault>,
+}
+
+#[derive(Accounts)]
+pub struct GetBettorHistory<'info> {
     pub market: Account<'info, Market>,
-    #[account(mut)]
-    pub bet_account: Account<'info, BetAccount>,
-    pub claimant: Signer<'info>,
-    #[account(mut)]
-    pub vault_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub claimant_token_account: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct AddLiquidity<'info> {
-    #[account(mut)]
+pub struct GetPayoutQuote<'info> {
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewOdds<'info> {
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct GetQuotes<'info> {
     pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteBet<'info> {
     pub vault: Account<'info, Vault>,
-    #[account(mut)]
-    pub provider: Signer<'info>,
-    #[account(mut)]
-    pub provider_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub vault_token_account: Account<'info, TokenAccount>,
-    pub lp_token_supply: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
+    pub market: Account<'info, Market>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Account state decoded from zeroed bytes, as Anchor reads a freshly allocated account
+    fn blank<T: AnchorDeserialize>() -> T {
+        let zeros = vec![0u8; 2 * size_of::<T>() + 64];
+        T::deserialize(&mut &zeros[..]).unwrap()
+    }
+
+    /// Binary market open for betting over `[100, 1_000)`
+    fn open_market() -> Market {
+        let mut market: Market = blank();
+        market.outcome_count = 2;
+        market.creation_timestamp = 0;
+        market.opening_time = 100;
+        market.resolution_time = 1_000;
+        market.min_bet_amount = 10;
+        market.is_tradable = true;
+        market
+    }
+
+    fn assert_error<T>(result: Result<T>, code: ErrorCode) {
+        match result {
+            Ok(_) => panic!("expected {:?}", code),
+            Err(error) => assert_eq!(error, Error::from(code)),
+        }
+    }
+
+    #[test]
+    fn bet_window_rejects_small_and_settled_bets() {
+        let mut market = open_market();
+        assert_error(validate_bet_window(&market, 9, 500, false), ErrorCode::BetTooSmall);
+        // The creator's seed bet may land before opening_time
+        assert!(validate_bet_window(&market, 10, 50, true).is_ok());
+        market.is_resolved = true;
+        assert_error(validate_bet_window(&market, 10, 500, false), ErrorCode::MarketResolved);
+    }
+
+    #[test]
+    fn early_exit_fee_shrinks_to_zero_at_resolution() {
+        let mut market = open_market();
+        assert_eq!(calculate_early_exit_fee(10_000, &market, 0, 500), 500);
+        assert_eq!(calculate_early_exit_fee(10_000, &market, 500, 500), 250);
+        assert_eq!(calculate_early_exit_fee(10_000, &market, 999, 500), 0);
+        // Past the lock the LP exits at full value, resolved or not
+        assert_eq!(calculate_early_exit_fee(10_000, &market, 1_000, 500), 0);
+        market.is_resolved = true;
+        assert_eq!(calculate_early_exit_fee(10_000, &market, 0, 500), 0);
+    }
+
+    #[test]
+    fn lp_withdrawals_pay_the_exit_fee_only_before_the_lock() {
+        let mut vault: Vault = blank();
+        vault.early_exit_fee_basis_points = 500;
+        vault.total_liquidity_locked = 10_000;
+        let mut market = open_market();
+        market.liquidity_locked = 10_000;
+        market.total_lp_tokens = 10_000;
+
+        // The withheld 50 stays locked for the remaining LPs
+        assert_eq!(withdraw_lp_liquidity(&mut vault, &mut market, 1_000, 0).unwrap(), (950, 50));
+        assert_eq!((market.liquidity_locked, market.total_lp_tokens), (9_050, 9_000));
+        assert_eq!(vault.total_liquidity_locked, 9_050);
+
+        // Past resolution_time an unresolved market charges nothing
+        assert_eq!(withdraw_lp_liquidity(&mut vault, &mut market, 900, 1_000).unwrap(), (905, 0));
+
+        market.is_resolved = true;
+        snapshot_lp_entitlements(&mut market);
+        assert_eq!(
+            withdraw_lp_liquidity(&mut vault, &mut market, 8_100, 1_000).unwrap(),
+            (8_145, 0)
+        );
+        assert_eq!((market.liquidity_locked, market.total_lp_tokens), (0, 0));
+        assert_error(
+            withdraw_lp_liquidity(&mut vault, &mut market, 1, 1_000),
+            ErrorCode::LpEntitlementExceeded
+        );
+    }
+
+    #[test]
+    fn lp_supply_tracks_liquidity_across_deposits_and_partial_withdrawals() {
+        // The first provider is minted one LP token per unit deposited
+        assert_eq!(calculate_lp_tokens(1_000, 0, 0).unwrap(), 1_000);
+
+        // Accrued fees have lifted each LP token to 1.2 units
+        let mut vault: Vault = blank();
+        vault.total_liquidity_locked = 1_200;
+        let mut market = open_market();
+        market.liquidity_locked = 1_200;
+        market.total_lp_tokens = 1_000;
+
+        let minted =
+            calculate_lp_tokens(600, market.liquidity_locked, market.total_lp_tokens).unwrap();
+        assert_eq!(minted, 500);
+        market.liquidity_locked += 600;
+        market.total_lp_tokens += minted;
+        vault.total_liquidity_locked += 600;
+        // Burning exactly what was minted returns the deposit
+        assert_eq!(
+            calculate_withdrawal_amount(minted, market.liquidity_locked, market.total_lp_tokens)
+                .unwrap(),
+            600
+        );
+
+        // A partial exit leaves the per-token value where it was
+        assert_eq!(withdraw_lp_liquidity(&mut vault, &mut market, 200, 0).unwrap(), (240, 0));
+        assert_eq!((market.liquidity_locked, market.total_lp_tokens), (1_560, 1_300));
+        assert_eq!(vault.total_liquidity_locked, 1_560);
+
+        // Both directions round down, so a round trip never returns more than went in
+        let dust = calculate_lp_tokens(7, 1_560, 1_300).unwrap();
+        assert_eq!(dust, 5);
+        assert_eq!(calculate_withdrawal_amount(dust, 1_567, 1_305).unwrap(), 6);
+    }
+
+    #[test]
+    fn early_exit_fee_is_capped_by_program_config() {
+        let mut config: ProgramConfig = blank();
+        config.max_fee_basis_points = MAX_FEE_BPS;
+        assert!(validate_early_exit_fee(MAX_FEE_BPS, &config).is_ok());
+        assert_error(validate_early_exit_fee(MAX_FEE_BPS + 1, &config), ErrorCode::InvalidFee);
+        config.max_fee_basis_points = 200;
+        assert_error(validate_early_exit_fee(201, &config), ErrorCode::InvalidFee);
+    }
+
+    #[test]
+    fn allowlist_proofs_verify_only_untampered_members() {
+        let keys = [1u8, 2, 3].map(|byte| Pubkey::new_from_array([byte; 32]));
+        let [a, b, c] = keys.map(|key| merkle_leaf(&key));
+        let ab = hash_merkle_pair(&a, &b);
+        let root = hash_merkle_pair(&ab, &c);
+
+        let proof_a = [b, c].concat();
+        assert!(verify_merkle_proof(&proof_a, root, keys[0]).is_ok());
+        assert!(verify_merkle_proof(&ab, root, keys[2]).is_ok());
+
+        let mut tampered = proof_a.clone();
+        tampered[0] ^= 1;
+        assert_error(verify_merkle_proof(&tampered, root, keys[0]), ErrorCode::InvalidMerkleProof);
+        assert_error(verify_merkle_proof(&proof_a, root, keys[1]), ErrorCode::InvalidMerkleProof);
+        assert_error(
+            verify_merkle_proof(&proof_a[..31], root, keys[0]),
+            ErrorCode::InvalidMerkleProof
+        );
+    }
+
+    #[test]
+    fn lmsr_prices_follow_the_quantity_gap() {
+        assert_eq!(calculate_lmsr_probability(0, 0, 1_000), 5_000);
+        assert_eq!(calculate_lmsr_probability(5_000, 5_000, 1_000), 5_000);
+        let (lower, higher) = (
+            calculate_lmsr_probability(300, 0, 1_000),
+            calculate_lmsr_probability(600, 0, 1_000),
+        );
+        assert!(5_000 < lower && lower < higher);
+        // A gap far beyond the liquidity pins the price instead of overflowing
+        assert_eq!(calculate_lmsr_probability(u64::MAX, 0, 1), 10_000);
+        // Without liquidity the price falls back to the pool split
+        assert_eq!(calculate_lmsr_probability(300, 100, 0), 7_500);
+    }
+
+    #[test]
+    fn lmsr_trade_charges_the_cost_function_difference() {
+        // C(q) = b * ln(exp(q_yes / b) + exp(q_no / b)) with b = 1_000
+        let cost_function =
+            |yes: f64, no: f64| 1_000.0 * ((yes / 1_000.0).exp() + (no / 1_000.0).exp()).ln();
+        let trade = calculate_lmsr_trade([0, 0], Outcome::Yes, 620, 1_000).unwrap();
+        assert_eq!(trade.shares, 999);
+        assert_eq!(trade.yes_probability, 7_309);
+        // Shares are rounded down, so the cost never exceeds what was paid
+        let charged = cost_function(999.0, 0.0) - cost_function(0.0, 0.0);
+        assert!(charged <= 620.0 && charged > 619.0);
+
+        let trade = calculate_lmsr_trade([0, 0], Outcome::No, 620, 1_000).unwrap();
+        assert_eq!(trade.shares, 999);
+        assert_eq!(trade.yes_probability, 2_691);
+        assert_eq!(calculate_lmsr_trade([0, 0], Outcome::Yes, 620, 0).unwrap().shares, 620);
+    }
+
+    #[test]
+    fn nullifiers_are_deterministic_and_market_scoped() {
+        let secret = [9u8; 32];
+        let nullifier = derive_nullifier(&secret, &[1u8; 32]);
+        assert_eq!(nullifier, derive_nullifier(&secret, &[1u8; 32]));
+        // Reusing a secret in another market yields an unrelated nullifier
+        assert_ne!(nullifier, derive_nullifier(&secret, &[2u8; 32]));
+        assert_ne!(nullifier, derive_nullifier(&[8u8; 32], &[1u8; 32]));
+        assert_eq!(nullifier, hashv(&[&secret, &[1u8; 32]]).to_bytes());
+    }
+
+    #[test]
+    fn multiproof_covers_three_leaves_with_shared_siblings() {
+        let [l0, l1, l2, l3] =
+            [1u8, 2, 3, 4].map(|byte| merkle_leaf(&Pubkey::new_from_array([byte; 32])));
+        let root = hash_merkle_pair(&hash_merkle_pair(&l0, &l1), &hash_merkle_pair(&l2, &l3));
+        // (l0, l1) from the queue, (l2, sibling l3), then the two computed nodes
+        let flags = [true, false, true];
+        assert!(verify_merkle_multiproof(&[l0, l1, l2], &[l3], &flags, root).is_ok());
+
+        let mut tampered = l2;
+        tampered[0] ^= 1;
+        assert_error(
+            verify_merkle_multiproof(&[l0, l1, tampered], &[l3], &flags, root),
+            ErrorCode::InvalidMerkleProof
+        );
+        assert_error(
+            verify_merkle_multiproof(&[l0, l1, l2], &[l3], &[true, true, false], root),
+            ErrorCode::InvalidMerkleProof
+        );
+        assert_error(
+            verify_merkle_multiproof(&[l0, l1, l2], &[l3, l3], &flags, root),
+            ErrorCode::InvalidMerkleProof
+        );
+    }
+
+    #[test]
+    fn proof_depth_is_bounded_before_hashing() {
+        assert!(require_proof_depth(&[0u8; 64], 2).is_ok());
+        assert_error(require_proof_depth(&[0u8; 65], 2), ErrorCode::ProofTooDeep);
+    }
+
+    #[test]
+    fn lockup_horizon_bounds_the_sum_of_windows() {
+        let mut vault: Vault = blank();
+        vault.resolution_extension_seconds = 100;
+        vault.max_resolution_extensions = 3;
+        vault.max_total_extension_seconds = 250;
+        vault.community_vote_window_seconds = 10;
+        vault.authority_override_window_seconds = 20;
+        vault.challenge_window_seconds = 30;
+        vault.cancellation_grace_seconds = 40;
+        // Extensions count up to their total cap, not 3 * 100
+        assert_eq!(configured_lockup_seconds(&vault), 350);
+
+        vault.max_total_lockup_seconds = 350;
+        assert!(require_lockup_horizon(&vault).is_ok());
+        vault.max_total_lockup_seconds = 349;
+        assert_error(require_lockup_horizon(&vault), ErrorCode::LockupHorizonExceeded);
+
+        vault.max_total_lockup_seconds = u64::MAX - 1;
+        vault.challenge_window_seconds = u64::MAX;
+        assert_error(require_lockup_horizon(&vault), ErrorCode::LockupHorizonExceeded);
+    }
+
+    /// Binary market resolved to `winner` over the given Yes/No pools
+    fn resolved_market(pools: [u64; 2], winner: Outcome) -> Market {
+        let mut market = open_market();
+        market.outcome_totals[..2].copy_from_slice(&pools);
+        market.is_resolved = true;
+        market.winning_outcome = Some(winner);
+        market.winning_index = Some(winner.discriminant());
+        market
+    }
+
+    #[test]
+    fn unbacked_winner_refunds_every_stake_and_fee() {
+        let mut market = resolved_market([0, 400], Outcome::Yes);
+        assert!(is_no_contest(&market));
+        assert_eq!(stake_entitlement(&market, 150, Outcome::No.discriminant()).unwrap(), Some(150));
+
+        let mut vault: Vault = blank();
+        vault.total_fees_collected = 100;
+        let mut bet: BetAccount = blank();
+        bet.fee_amount = 30;
+        // The resolver already collected the keeper pool, so the tip stays paid
+        bet.keeper_tip = 5;
+        assert_eq!(refund_bet_fee(&mut vault, &mut market, &bet).unwrap(), 30);
+        assert_eq!(vault.total_fees_collected, 70);
+        // Fees already withdrawn cannot fund the refund
+        vault.total_fees_withdrawn = 70;
+        assert_error(
+            refund_bet_fee(&mut vault, &mut market, &bet),
+            ErrorCode::InsufficientFeesForRefund
+        );
+    }
+
+    #[test]
+    fn void_refunds_owe_no_gross_winnings_fee() {
+        let mut vault: Vault = blank();
+        vault.winnings_fee_basis_points = 500;
+        vault.winnings_fee_basis = WinningsFeeBasis::Gross;
+        let mut market = resolved_market([300, 600], Outcome::Yes);
+        assert_eq!(take_winnings_fee(&mut vault, &market, 300, 100).unwrap(), 15);
+
+        market.is_void = true;
+        let refund = stake_entitlement(&market, 100, Outcome::No.discriminant()).unwrap();
+        assert_eq!(refund, Some(100));
+        let fee = take_winnings_fee(&mut vault, &market, 100, 100).unwrap();
+        assert_eq!(net_winnings(100, fee, 0).unwrap(), 100);
+        assert_eq!(vault.total_fees_collected, 15);
+    }
+
+    #[test]
+    fn cancelled_market_refunds_keeper_tips_and_can_be_closed() {
+        let mut market = open_market();
+        market.outcome_totals[..2].copy_from_slice(&[300, 100]);
+        market.keeper_pool = 40;
+        market.is_resolved = true;
+        market.is_void = true;
+        market.is_cancelled = true;
+        market.total_claimable = 400;
+        let mut vault: Vault = blank();
+        vault.total_fees_collected = 8;
+
+        let mut bets = [blank::<BetAccount>(), blank()];
+        for (bet, (stake, fee, tip)) in bets.iter_mut().zip([(300, 6, 30), (100, 2, 10)]) {
+            bet.amount = stake;
+            bet.fee_amount = fee;
+            bet.keeper_tip = tip;
+        }
+        let refunds = bets.map(|bet| {
+            let stake = bet_entitlement(&market, &bet).unwrap().unwrap();
+            market.total_claimed += stake;
+            stake + refund_bet_fee(&mut vault, &mut market, &bet).unwrap()
+        });
+        // Every bettor gets back all they paid in: stake, fee and tip
+        assert_eq!(refunds, [336, 112]);
+        assert_eq!((market.keeper_pool, vault.total_fees_collected), (0, 0));
+        assert!(market_fully_settled(&market));
+    }
+
+    #[test]
+    fn winners_split_the_pool_and_losers_get_nothing() {
+        let mut market = resolved_market([300, 600], Outcome::Yes);
+        assert!(!is_no_contest(&market));
+        let yes = Outcome::Yes.discriminant();
+        let no = Outcome::No.discriminant();
+        assert_eq!(stake_entitlement(&market, 100, yes).unwrap(), Some(300));
+        assert_eq!(stake_entitlement(&market, 100, no).unwrap(), None);
+
+        // Burned stake leaves the pool before it is shared out
+        market.burned_amount = 90;
+        assert_eq!(stake_entitlement(&market, 100, yes).unwrap(), Some(270));
+
+        // A single-sided market pays its winners back exactly their stake
+        let market = resolved_market([500, 0], Outcome::Yes);
+        assert!(!is_no_contest(&market));
+        assert_eq!(stake_entitlement(&market, 200, yes).unwrap(), Some(200));
+
+        let mut market = resolved_market([300, 600], Outcome::Yes);
+        market.is_void = true;
+        assert!(is_no_contest(&market));
+        assert_eq!(stake_entitlement(&market, 100, no).unwrap(), Some(100));
+    }
+
+    #[test]
+    fn community_votes_settle_on_any_outcome_index() {
+        let mut market = open_market();
+        market.outcome_count = 3;
+        market.outcome_totals[..3].copy_from_slice(&[200, 300, 500]);
+        market.community_quorum_basis_points = 5_000;
+        assert!(require_valid_outcome(market_pools(&market), 2).is_ok());
+        assert_error(require_valid_outcome(market_pools(&market), 3), ErrorCode::InvalidOutcome);
+
+        // 400 of 1_000 staked falls short of the 50% quorum
+        market.resolution_votes[..3].copy_from_slice(&[100, 0, 300]);
+        assert!(community_vote_result(&market).is_none());
+        market.resolution_votes[..3].copy_from_slice(&[100, 0, 400]);
+        assert_eq!(community_vote_result(&market), Some(2));
+        // Quorum without a strict majority settles nothing
+        market.resolution_votes[..3].copy_from_slice(&[250, 0, 250]);
+        assert!(community_vote_result(&market).is_none());
+    }
+
+    #[test]
+    fn bettor_history_buckets_stake_by_outcome_index() {
+        let mut staked = [0u64; 3];
+        let mut bet: BetAccount = blank();
+        for (index, amount) in [(1u8, 40), (2, 25), (2, 5)] {
+            bet.outcome_index = index;
+            bet.amount = amount;
+            add_outcome_stake(&mut staked, &bet).unwrap();
+        }
+        assert_eq!(staked, [0, 40, 30]);
+        bet.outcome_index = 3;
+        assert_error(add_outcome_stake(&mut staked, &bet), ErrorCode::InvalidOutcome);
+    }
+
+    #[test]
+    fn only_binary_markets_map_outcome_indexes_to_yes_no() {
+        let mut market = open_market();
+        assert!(binary_outcome(&market, 1).unwrap() == Some(Outcome::No));
+        market.outcome_count = 3;
+        assert!(binary_outcome(&market, 2).unwrap().is_none());
+        assert!(binary_outcome(&market, 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn claim_deadline_runs_from_the_later_unlock() {
+        let mut market = resolved_market([300, 600], Outcome::Yes);
+        market.claims_unlock_time = 1_100;
+        market.resolution_finalizes_at = 1_200;
+        assert_eq!(claim_deadline(&market, 0, 1_000).unwrap(), 0);
+        assert_eq!(claim_deadline(&market, 500, 1_000).unwrap(), 1_700);
+        // A late settlement starts the window from the settlement itself
+        assert_eq!(claim_deadline(&market, 500, 1_500).unwrap(), 2_000);
+
+        market.claim_deadline = 1_700;
+        assert_error(require_claims_open(&market, 1_150), ErrorCode::MarketUnderChallenge);
+        assert!(require_claims_open(&market, 1_699).is_ok());
+        assert_error(require_claims_open(&market, 1_700), ErrorCode::ClaimWindowExpired);
+    }
+
+    #[test]
+    fn disputes_fit_inside_the_challenge_window() {
+        let mut market = resolved_market([300, 600], Outcome::Yes);
+        market.resolution_finalizes_at = 1_200;
+        assert!(require_disputable(&market, 1_199).is_ok());
+        assert_error(require_disputable(&market, 1_200), ErrorCode::DisputeWindowClosed);
+        market.is_disputed = true;
+        assert_error(require_disputable(&market, 1_100), ErrorCode::MarketAlreadyDisputed);
+
+        // Settlements without a correctable outcome never open a window to dispute
+        let settlements: [fn(&mut Market); 3] = [
+            |market| market.is_probabilistic = true,
+            |market| market.community_resolved = true,
+            |market| market.winning_index = None,
+        ];
+        for settle in settlements {
+            let mut market = resolved_market([300, 600], Outcome::Yes);
+            market.resolution_finalizes_at = 1_200;
+            settle(&mut market);
+            assert_error(require_disputable(&market, 1_100), ErrorCode::DisputeNotAllowed);
+        }
+    }
+
+    #[test]
+    fn claims_wait_out_the_challenge_window_and_disputes() {
+        let mut market = resolved_market([300, 600], Outcome::Yes);
+        market.resolution_finalizes_at = 1_200;
+        assert_error(require_claims_open(&market, 1_199), ErrorCode::MarketUnderChallenge);
+        assert!(require_claims_open(&market, 1_200).is_ok());
+
+        // A dispute freezes claims past the window until the authority rules on it
+        market.is_disputed = true;
+        assert_error(require_claims_open(&market, 5_000), ErrorCode::MarketUnderChallenge);
+        market.is_disputed = false;
+        market.resolution_finalizes_at = 5_000;
+        assert!(require_claims_open(&market, 5_000).is_ok());
+    }
+
+    #[test]
+    fn claim_window_counts_toward_the_lockup_horizon() {
+        let mut vault: Vault = blank();
+        vault.challenge_window_seconds = 100;
+        vault.claim_window_seconds = 200;
+        assert_eq!(configured_lockup_seconds(&vault), 300);
+        vault.max_total_lockup_seconds = 299;
+        assert_error(require_lockup_horizon(&vault), ErrorCode::LockupHorizonExceeded);
+    }
+
+    fn label(text: &str) -> [u8; 16] {
+        let mut label = [0u8; 16];
+        label[..text.len()].copy_from_slice(text.as_bytes());
+        label
+    }
+
+    #[test]
+    fn bet_quote_matches_the_booked_bet() {
+        let vault: Vault = blank();
+        let mut market = open_market();
+        market.outcome_count = 3;
+        market.outcome_totals[..3].copy_from_slice(&[200, 300, 500]);
+        market.fee_basis_points = 100;
+        market.burn_basis_points = 1_000;
+
+        let quote = project_bet(&vault, &market, 100, 0, 500).unwrap();
+        assert_eq!((quote.fee_amount, quote.stake, quote.shares), (1, 99, 99));
+        // Quoting leaves the market untouched
+        assert_eq!(market_pools(&market), &[200, 300, 500]);
+
+        // Booking the same bet and settling on it pays exactly the quoted payout
+        let pricing = price_bet(&vault, &market, 100, 0, false, 500).unwrap();
+        apply_bet(&mut market, 0, &pricing).unwrap();
+        assert_eq!(market_pools(&market), &[299, 300, 500]);
+        market.burned_amount = calculate_resolution_burn(market_pools(&market), 0, 1_000).unwrap();
+        assert_eq!(market.burned_amount, 80);
+        market.is_resolved = true;
+        market.winning_index = Some(0);
+        assert_eq!(stake_entitlement(&market, 99, 0).unwrap(), Some(quote.payout));
+        assert_eq!(quote.payout, 337);
+    }
+
+    #[test]
+    fn positions_transfer_only_while_open_and_to_a_new_owner() {
+        let seller = Pubkey::new_from_array([1; 32]);
+        let buyer = Pubkey::new_from_array([2; 32]);
+        let mut market = open_market();
+        let mut bet: BetAccount = blank();
+        bet.bettor = seller;
+        assert!(require_transferable(&market, &bet, &seller, &buyer).is_ok());
+        assert_error(require_transferable(&market, &bet, &buyer, &buyer), ErrorCode::Unauthorized);
+        assert_error(
+            require_transferable(&market, &bet, &seller, &seller),
+            ErrorCode::InvalidPositionOwner
+        );
+        assert_error(
+            require_transferable(&market, &bet, &seller, &Pubkey::default()),
+            ErrorCode::InvalidPositionOwner
+        );
+        bet.is_claimed = true;
+        assert_error(
+            require_transferable(&market, &bet, &seller, &buyer),
+            ErrorCode::AlreadyClaimed
+        );
+        bet.is_claimed = false;
+        market.is_resolved = true;
+        assert_error(
+            require_transferable(&market, &bet, &seller, &buyer),
+            ErrorCode::MarketResolved
+        );
+
+        // Once transferred, a private market's claim follows the new owner's signature
+        market.privacy_enabled = true;
+        bet.bettor = buyer;
+        bet.ownership_transferred = true;
+        assert!(verify_claim_ownership(&market, &bet, &buyer, &[]).is_ok());
+        assert_error(verify_claim_ownership(&market, &bet, &seller, &[]), ErrorCode::Unauthorized);
+    }
+
+    #[test]
+    fn private_claims_still_require_the_bettor() {
+        let bettor = Pubkey::new_from_array([1; 32]);
+        let stranger = Pubkey::new_from_array([2; 32]);
+        let mut market = open_market();
+        market.privacy_enabled = true;
+        let mut bet: BetAccount = blank();
+        bet.bettor = bettor;
+        assert!(verify_claim_ownership(&market, &bet, &bettor, &[]).is_ok());
+        assert_error(
+            verify_claim_ownership(&market, &bet, &stranger, &[]),
+            ErrorCode::Unauthorized
+        );
+    }
+
+    #[test]
+    fn linear_fee_curve_rises_to_its_maximum_at_resolution() {
+        let mut market = open_market();
+        market.fee_basis_points = 100;
+        market.fee_curve_max_basis_points = 500;
+        assert_eq!(effective_fee_basis_points(&market, 500), 100);
+
+        market.fee_curve = FeeCurve::Linear;
+        let fees =
+            [-50, 0, 500, 999, 1_000, 5_000].map(|now| effective_fee_basis_points(&market, now));
+        assert_eq!(fees, [100, 100, 300, 499, 500, 500]);
+
+        let vault: Vault = blank();
+        let pricing = price_bet(&vault, &market, 1_000, 0, false, 500).unwrap();
+        assert_eq!((pricing.fee_basis_points, pricing.fee_amount), (300, 30));
+        assert_eq!(price_bet(&vault, &market, 1_000, 0, true, 500).unwrap().fee_amount, 0);
+
+        // The curve never charges past the program-wide fee ceiling
+        market.fee_basis_points = 900;
+        market.fee_curve_max_basis_points = 2_000;
+        assert_eq!(effective_fee_basis_points(&market, 1_000), MAX_FEE_BPS);
+    }
+
+    #[test]
+    fn fixed_fees_answer_only_to_the_config_ceiling() {
+        let mut config: ProgramConfig = blank();
+        config.max_fee_basis_points = 2_000;
+        // Governance raised the ceiling past MAX_FEE_BPS; a fixed fee may use it
+        assert_eq!(validate_fee_curve(1_500, None, FeeCurve::Fixed, &config).unwrap().1, 1_500);
+        let (_, max) = validate_fee_curve(1_500, Some(100), FeeCurve::Fixed, &config).unwrap();
+        assert_eq!(max, 1_500);
+        assert_error(
+            validate_fee_curve(2_001, None, FeeCurve::Fixed, &config),
+            ErrorCode::InvalidFee
+        );
+
+        assert_eq!(validate_fee_curve(100, Some(500), FeeCurve::Linear, &config).unwrap().1, 500);
+        assert_error(
+            validate_fee_curve(100, Some(MAX_FEE_BPS + 1), FeeCurve::Linear, &config),
+            ErrorCode::InvalidFee
+        );
+        assert_error(
+            validate_fee_curve(500, Some(100), FeeCurve::Linear, &config),
+            ErrorCode::InvalidFee
+        );
+    }
+
+    #[test]
+    fn outcome_schemas_need_distinct_labels() {
+        let labels = ["Home", "Away", "Draw"].map(label);
+        assert!(validate_outcome_labels(&labels).is_ok());
+        assert_error(validate_outcome_labels(&labels[..1]), ErrorCode::InvalidOutcomeSchema);
+        assert_error(
+            validate_outcome_labels(&[label("Home"), label("Home")]),
+            ErrorCode::InvalidOutcomeSchema
+        );
+        assert_error(
+            validate_outcome_labels(&[label("Home"), [0u8; 16]]),
+            ErrorCode::InvalidOutcomeSchema
+        );
+        assert_error(
+            validate_outcome_labels(&[label("Home"); MAX_OUTCOMES + 1]),
+            ErrorCode::InvalidOutcomeSchema
+        );
+    }
+
+    #[test]
+    fn three_way_schema_market_settles_on_draw() {
+        let vault_key = Pubkey::new_from_array([7; 32]);
+        let mut schema: OutcomeSchema = blank();
+        schema.vault = vault_key;
+        schema.outcome_count = 3;
+        schema.labels[..3].copy_from_slice(&["Home", "Away", "Draw"].map(label));
+        assert!(require_outcome_schema(&schema, &vault_key, 3).is_ok());
+        assert_error(
+            require_outcome_schema(&schema, &vault_key, 2),
+            ErrorCode::InvalidOutcomeSchema
+        );
+        assert_error(
+            require_outcome_schema(&schema, &Pubkey::default(), 3),
+            ErrorCode::InvalidOutcomeSchema
+        );
+
+        let vault: Vault = blank();
+        let mut market = open_market();
+        market.outcome_count = 3;
+        for (index, amount) in [(0u8, 300), (1, 500), (2, 200)] {
+            let pricing = price_bet(&vault, &market, amount, index, false, 500).unwrap();
+            apply_bet(&mut market, index, &pricing).unwrap();
+        }
+        assert_eq!(market_pools(&market), &[300, 500, 200]);
+        assert_error(price_bet(&vault, &market, 100, 3, false, 500), ErrorCode::InvalidOutcome);
+
+        let draw = schema.labels.iter().position(|&text| text == label("Draw")).unwrap();
+        market.is_resolved = true;
+        market.winning_index = Some(draw as u8);
+        // The whole 1_000 pool goes to the Draw backers in proportion to their stake
+        assert_eq!(stake_entitlement(&market, 200, 2).unwrap(), Some(1_000));
+        assert_eq!(stake_entitlement(&market, 50, 2).unwrap(), Some(250));
+        assert_eq!(stake_entitlement(&market, 500, 1).unwrap(), None);
+    }
+
+    #[cfg(feature = "test-clock")]
+    #[test]
+    fn mock_clock_crosses_bet_window_boundaries() {
+        let market = open_market();
+        let bet_at = |timestamp| {
+            set_mock_timestamp(Some(timestamp));
+            let now = current_timestamp().unwrap();
+            assert_eq!(now, timestamp);
+            validate_bet_window(&market, 10, now, false)
+        };
+
+        assert_error(bet_at(99), ErrorCode::MarketNotOpenYet);
+        assert!(bet_at(100).is_ok());
+        assert!(bet_at(999).is_ok());
+        assert_error(bet_at(1_000), ErrorCode::MarketClosed);
+        assert!(market_phase(&market, current_timestamp().unwrap()) == MarketPhase::Closed);
+
+        set_mock_timestamp(None);
+    }
 }